@@ -0,0 +1,160 @@
+use crate::{HexDir, HexRegion, Hexagon, IHex, Line, Point, Rect, Region, Rotated, Shape};
+
+/// Any of the shapes of this crate, to keep different shapes in one collection.
+#[derive(Clone, Debug)]
+pub enum AnyShape {
+    Point(Point),
+    Line(Line),
+    Rect(Rect),
+    Hexagon(Hexagon),
+    Rotated(Box<Rotated<AnyShape>>),
+    Region(Box<Region>),
+    HexRegion(HexRegion),
+}
+
+/// call `$f` with the shape inside of any variant
+macro_rules! any {
+    ($shape:expr, $s:ident => $f:expr) => {
+        match $shape {
+            AnyShape::Point($s) => $f,
+            AnyShape::Line($s) => $f,
+            AnyShape::Rect($s) => $f,
+            AnyShape::Hexagon($s) => $f,
+            AnyShape::Rotated($s) => $f,
+            AnyShape::Region($s) => $f,
+            AnyShape::HexRegion($s) => $f,
+        }
+    };
+}
+
+impl Shape for AnyShape {
+    fn center(&self) -> IHex {
+        any!(self, s => s.center())
+    }
+
+    fn area(&self) -> usize {
+        any!(self, s => s.area())
+    }
+
+    fn hex_iter(&self) -> impl Iterator<Item = IHex> {
+        let iter: Box<dyn Iterator<Item = IHex> + '_> = any!(self, s => Box::new(s.hex_iter()));
+        iter
+    }
+
+    fn hex_by_ind(&self, ind: usize) -> Option<IHex> {
+        any!(self, s => s.hex_by_ind(ind))
+    }
+
+    fn ind_by_hex(&self, hex: IHex) -> Option<usize> {
+        any!(self, s => s.ind_by_hex(hex))
+    }
+
+    fn contains(&self, hex: IHex) -> bool {
+        any!(self, s => s.contains(hex))
+    }
+
+    fn move_to(&self, hex: IHex) -> Self {
+        any!(self, s => s.move_to(hex).into())
+    }
+
+    fn all_neighbors(&self) -> impl Iterator<Item = IHex> {
+        let iter: Box<dyn Iterator<Item = IHex> + '_> =
+            any!(self, s => Box::new(s.all_neighbors()));
+        iter
+    }
+
+    fn direction_neighbors(&self, d: HexDir) -> impl Iterator<Item = IHex> {
+        let iter: Box<dyn Iterator<Item = IHex> + '_> =
+            any!(self, s => Box::new(s.direction_neighbors(d)));
+        iter
+    }
+}
+
+impl From<Point> for AnyShape {
+    fn from(s: Point) -> Self {
+        AnyShape::Point(s)
+    }
+}
+
+impl From<Line> for AnyShape {
+    fn from(s: Line) -> Self {
+        AnyShape::Line(s)
+    }
+}
+
+impl From<Rect> for AnyShape {
+    fn from(s: Rect) -> Self {
+        AnyShape::Rect(s)
+    }
+}
+
+impl From<Hexagon> for AnyShape {
+    fn from(s: Hexagon) -> Self {
+        AnyShape::Hexagon(s)
+    }
+}
+
+impl From<Region> for AnyShape {
+    fn from(s: Region) -> Self {
+        AnyShape::Region(Box::new(s))
+    }
+}
+
+impl From<HexRegion> for AnyShape {
+    fn from(s: HexRegion) -> Self {
+        AnyShape::HexRegion(s)
+    }
+}
+
+impl<S: Shape + Clone + Into<AnyShape>> From<Rotated<S>> for AnyShape {
+    fn from(s: Rotated<S>) -> Self {
+        let inner = s.inner().clone().into();
+        AnyShape::Rotated(Box::new(Rotated::new(inner, s.steps())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn mixed_collection() {
+        let shapes: Vec<AnyShape> = vec![
+            Point(IHex(5, 5)).into(),
+            Line::new(IHex(0, 0), IHex(3, 0)).into(),
+            Rect::new(IHex(-2, 1), 3, 2).into(),
+            Hexagon::new(IHex(1, -4), 1).into(),
+            Rect::new(IHex(0, 0), 3, 2).rotate(2).into(),
+        ];
+        let areas: Vec<usize> = shapes.iter().map(|s| s.area()).collect();
+        assert_eq!(areas, vec![1, 4, 6, 7, 6]);
+        for s in &shapes {
+            for (i, h) in s.hex_iter().enumerate() {
+                assert!(s.contains(h));
+                assert_eq!(s.hex_by_ind(i), Some(h));
+                assert_eq!(s.ind_by_hex(h), Some(i));
+            }
+            let moved = s.move_to(IHex(10, -3));
+            assert_eq!(moved.center(), IHex(10, -3));
+            assert_eq!(moved.area(), s.area());
+        }
+    }
+
+    #[test]
+    fn same_as_inner_shape() {
+        let rect = Rect::with_type(IHex(1, 2), 4, 3, LayoutType::Flat);
+        let any = AnyShape::from(rect);
+        assert!(any.hex_iter().eq(rect.hex_iter()));
+        assert!(any.all_neighbors().eq(rect.all_neighbors()));
+        for d in ALL_DIR {
+            assert!(any.direction_neighbors(d).eq(rect.direction_neighbors(d)));
+        }
+        // every side not shared with another hex of the rect
+        let shared = rect
+            .hex_iter()
+            .flat_map(|h| h.neighbors())
+            .filter(|n| rect.contains(*n))
+            .count();
+        assert_eq!(any.perimeter(), 6 * rect.area() - shared);
+    }
+}
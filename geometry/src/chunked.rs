@@ -0,0 +1,331 @@
+use crate::{Hexagon, IHex, LayoutType, MapShape, OffsetCoord, OffsetType, Rect, Shape};
+use std::collections::{HashMap, HashSet};
+
+/// Coordinates of a chunk in the lattice of chunks.
+pub type ChunkKey = (i32, i32);
+
+/// Largest radius of hexagon chunks, about three million tiles.
+pub const MAX_CHUNK_RADIUS: i32 = 1 << 10;
+
+/// Shape of the chunks tiling the axial plane, made by [`ChunkShape::rect`] or
+/// [`ChunkShape::hexagon`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ChunkShape(ChunkKind);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum ChunkKind {
+    /// rectangles of `w` columns and `h` rows, stacked along the shifted axis
+    Rect { w: i32, h: i32, t: LayoutType },
+    /// hexagons of given radius
+    Hexagon { radius: i32 },
+}
+
+impl ChunkShape {
+    /// Rect chunks must have an even number of shifted rows (pointy) or columns (flat),
+    /// otherwise neighboring chunks would not line up.
+    pub fn rect(w: i32, h: i32, t: LayoutType) -> Self {
+        assert!(w > 0 && h > 0, "chunk must not be empty");
+        match t {
+            LayoutType::Pointy => assert!(h % 2 == 0, "pointy chunk must have even rows"),
+            LayoutType::Flat => assert!(w % 2 == 0, "flat chunk must have even columns"),
+        }
+        ChunkShape(ChunkKind::Rect { w, h, t })
+    }
+
+    pub fn hexagon(radius: i32) -> Self {
+        assert!(radius >= 0, "chunk must not be empty");
+        assert!(
+            radius <= MAX_CHUNK_RADIUS,
+            "chunk radius must not exceed {MAX_CHUNK_RADIUS}"
+        );
+        ChunkShape(ChunkKind::Hexagon { radius })
+    }
+
+    /// number of tiles in a chunk
+    pub fn area(&self) -> usize {
+        match self.0 {
+            ChunkKind::Rect { w, h, .. } => w as usize * h as usize,
+            ChunkKind::Hexagon { radius } => Hexagon::new(IHex(0, 0), radius).area(),
+        }
+    }
+
+    /// key of the chunk containing the hex
+    pub fn key(&self, hex: IHex) -> ChunkKey {
+        match self.0 {
+            ChunkKind::Rect { w, h, t } => {
+                let OffsetCoord { col, row } = OffsetCoord::from_hex(hex, OffsetType::odd(t));
+                (col.div_euclid(w), row.div_euclid(h))
+            }
+            ChunkKind::Hexagon { radius } => {
+                // invert the lattice basis and pick the nearest of the cell corners,
+                // the products do not fit i32 for large radii or far hexes
+                let (n, q, r) = (radius as i64, hex.q() as i64, hex.r() as i64);
+                let det = 3 * n * n + 3 * n + 1;
+                let u = ((n + 1) * q - n * r).div_euclid(det) as i32;
+                let v = (n * q + (2 * n + 1) * r).div_euclid(det) as i32;
+                [(u, v), (u + 1, v), (u, v + 1), (u + 1, v + 1)]
+                    .into_iter()
+                    .find(|&key| self.hexagon_center(key).distance(hex) <= radius)
+                    .expect("centers form a triangular lattice, a cell corner is the nearest")
+            }
+        }
+    }
+
+    /// chunk with the given key
+    fn shape(&self, key: ChunkKey) -> MapShape {
+        match self.0 {
+            ChunkKind::Rect { w, h, t } => {
+                let h0 = OffsetCoord::new(key.0 * w, key.1 * h).to_hex(OffsetType::odd(t));
+                Rect::with_type(h0, w, h, t).into()
+            }
+            ChunkKind::Hexagon { radius } => Hexagon::new(self.hexagon_center(key), radius).into(),
+        }
+    }
+
+    #[inline]
+    fn hexagon_center(&self, key: ChunkKey) -> IHex {
+        let n = match self.0 {
+            ChunkKind::Hexagon { radius } => radius,
+            ChunkKind::Rect { .. } => 0,
+        };
+        IHex(2 * n + 1, -n) * key.0 + IHex(n, n + 1) * key.1
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Chunk<T> {
+    area: MapShape,
+    values: Vec<Option<T>>,
+    len: usize,
+}
+
+impl<T> Chunk<T> {
+    fn iter(&self) -> impl Iterator<Item = (IHex, &T)> {
+        self.values
+            .iter()
+            .enumerate()
+            .filter_map(|(i, v)| Some((self.area.hex_by_ind(i)?, v.as_ref()?)))
+    }
+}
+
+/// Unbounded sparse storage of hex tiles, split into lazily allocated chunks.
+#[derive(Clone, Debug)]
+pub struct ChunkedHexMap<T> {
+    chunk: ChunkShape,
+    chunks: HashMap<ChunkKey, Chunk<T>>,
+    len: usize,
+}
+
+impl<T> ChunkedHexMap<T> {
+    pub fn new(chunk: ChunkShape) -> Self {
+        ChunkedHexMap {
+            chunk,
+            chunks: HashMap::new(),
+            len: 0,
+        }
+    }
+
+    pub fn chunk_shape(&self) -> ChunkShape {
+        self.chunk
+    }
+
+    /// number of stored tiles
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// number of allocated chunks
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn get(&self, hex: IHex) -> Option<&T> {
+        let chunk = self.chunks.get(&self.chunk.key(hex))?;
+        chunk.values[chunk.area.ind_by_hex(hex)?].as_ref()
+    }
+
+    pub fn get_mut(&mut self, hex: IHex) -> Option<&mut T> {
+        let chunk = self.chunks.get_mut(&self.chunk.key(hex))?;
+        chunk.values[chunk.area.ind_by_hex(hex)?].as_mut()
+    }
+
+    pub fn contains(&self, hex: IHex) -> bool {
+        self.get(hex).is_some()
+    }
+
+    /// store value at the hex allocating its chunk if needed, returns the previous value
+    pub fn insert(&mut self, hex: IHex, value: T) -> Option<T> {
+        let key = self.chunk.key(hex);
+        let area = self.chunk.shape(key);
+        let ind = area
+            .ind_by_hex(hex)
+            .expect("hex must lie in the chunk of its key");
+        let len = self.chunk.area();
+        let chunk = self.chunks.entry(key).or_insert_with(|| Chunk {
+            area,
+            values: (0..len).map(|_| None).collect(),
+            len: 0,
+        });
+        let prev = chunk.values[ind].replace(value);
+        if prev.is_none() {
+            chunk.len += 1;
+            self.len += 1;
+        }
+        prev
+    }
+
+    /// take value out of the hex, the chunk stays allocated until `remove_empty_chunks`
+    pub fn remove(&mut self, hex: IHex) -> Option<T> {
+        let chunk = self.chunks.get_mut(&self.chunk.key(hex))?;
+        let prev = chunk.values[chunk.area.ind_by_hex(hex)?].take();
+        if prev.is_some() {
+            chunk.len -= 1;
+            self.len -= 1;
+        }
+        prev
+    }
+
+    /// free chunks without any tiles, returns number of freed chunks
+    pub fn remove_empty_chunks(&mut self) -> usize {
+        let before = self.chunks.len();
+        self.chunks.retain(|_, chunk| chunk.len > 0);
+        before - self.chunks.len()
+    }
+
+    /// all stored tiles grouped by chunk, chunks are in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (IHex, &T)> {
+        self.chunks.values().flat_map(|chunk| chunk.iter())
+    }
+
+    /// keys of allocated chunks intersecting the viewport, in viewport order
+    pub fn chunks_in(&self, viewport: &impl Shape) -> impl Iterator<Item = ChunkKey> {
+        let mut seen = HashSet::new();
+        viewport
+            .hex_iter()
+            .map(|hex| self.chunk.key(hex))
+            .filter(move |key| seen.insert(*key))
+            .filter(|key| self.chunks.contains_key(key))
+    }
+
+    /// stored tiles of a single chunk
+    pub fn chunk_tiles(&self, key: ChunkKey) -> impl Iterator<Item = (IHex, &T)> {
+        self.chunks
+            .get(&key)
+            .into_iter()
+            .flat_map(|chunk| chunk.iter())
+    }
+
+    /// stored tiles inside the viewport, grouped by chunk
+    pub fn iter_in(&self, viewport: &impl Shape) -> impl Iterator<Item = (IHex, &T)> {
+        self.chunks_in(viewport).flat_map(move |key| {
+            self.chunk_tiles(key)
+                .filter(move |(hex, _)| viewport.contains(*hex))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn chunk_shapes() -> [ChunkShape; 4] {
+        [
+            ChunkShape::rect(4, 6, LayoutType::Pointy),
+            ChunkShape::rect(6, 3, LayoutType::Flat),
+            ChunkShape::hexagon(2),
+            ChunkShape::hexagon(0),
+        ]
+    }
+
+    #[test]
+    fn chunks_tile_the_plane() {
+        for shape in chunk_shapes() {
+            let mut seen = vec![0; shape.area()];
+            for hex in Hexagon::new(IHex(0, 0), 15).hex_iter() {
+                let area = shape.shape(shape.key(hex));
+                let ind = area.ind_by_hex(hex).expect("hex must be in its chunk");
+                assert_eq!(area.hex_by_ind(ind), Some(hex));
+                if shape.key(hex) == (0, 0) {
+                    seen[ind] += 1;
+                }
+            }
+            assert!(seen.iter().all(|&n| n == 1), "{shape:?}");
+        }
+    }
+
+    #[test]
+    fn hexagon_keys() {
+        for radius in 0..6 {
+            let shape = ChunkShape::hexagon(radius);
+            for hex in Hexagon::new(IHex(-4, 9), 40).hex_iter() {
+                let center = shape.hexagon_center(shape.key(hex));
+                assert!(center.distance(hex) <= radius, "{hex:?} radius {radius}");
+            }
+        }
+    }
+
+    #[test]
+    fn far_hexagon_keys() {
+        for (radius, hex) in [
+            (MAX_CHUNK_RADIUS, IHex(5, 5)),
+            (MAX_CHUNK_RADIUS, IHex(-1 << 30, 1 << 29)),
+            (1000, IHex(3_000_000, 0)),
+            (1, IHex(1 << 30, -1 << 30)),
+        ] {
+            let shape = ChunkShape::hexagon(radius);
+            let center = shape.hexagon_center(shape.key(hex));
+            assert!(center.distance(hex) <= radius, "{hex:?} radius {radius}");
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "chunk radius")]
+    fn hexagon_radius_limit() {
+        ChunkShape::hexagon(MAX_CHUNK_RADIUS + 1);
+    }
+
+    #[test]
+    fn insert_get_remove() {
+        for shape in chunk_shapes() {
+            let mut m = ChunkedHexMap::new(shape);
+            assert_eq!(m.insert(IHex(100, -40), 1), None);
+            assert_eq!(m.insert(IHex(-7, 3), 2), None);
+            assert_eq!(m.insert(IHex(-7, 3), 3), Some(2));
+            assert_eq!(m.len(), 2);
+            assert_eq!(m.get(IHex(100, -40)), Some(&1));
+            assert_eq!(m.get(IHex(-7, 3)), Some(&3));
+            assert_eq!(m.get(IHex(0, 0)), None);
+            *m.get_mut(IHex(100, -40)).unwrap() += 10;
+            assert_eq!(m.remove(IHex(100, -40)), Some(11));
+            assert_eq!(m.remove(IHex(100, -40)), None);
+            assert_eq!(m.len(), 1);
+            assert_eq!(m.chunk_count(), 2);
+            assert_eq!(m.remove_empty_chunks(), 1);
+            assert_eq!(m.chunk_count(), 1);
+            assert_eq!(m.iter().collect::<Vec<_>>(), vec![(IHex(-7, 3), &3)]);
+        }
+    }
+
+    #[test]
+    fn iter_in_viewport() {
+        let mut m = ChunkedHexMap::new(ChunkShape::rect(8, 8, LayoutType::Pointy));
+        for hex in Hexagon::new(IHex(0, 0), 20).hex_iter() {
+            m.insert(hex, hex.distance(IHex(0, 0)));
+        }
+        let viewport = Rect::new(IHex(-3, -3), 10, 6);
+        let tiles = m.iter_in(&viewport).collect::<Vec<_>>();
+        assert_eq!(tiles.len(), viewport.area());
+        for (hex, v) in tiles {
+            assert!(viewport.contains(hex));
+            assert_eq!(*v, hex.distance(IHex(0, 0)));
+        }
+        let keys = m.chunks_in(&viewport).collect::<Vec<_>>();
+        assert_eq!(keys.len(), 4);
+        let far = Rect::new(IHex(500, 500), 4, 4);
+        assert_eq!(m.iter_in(&far).count(), 0);
+    }
+}
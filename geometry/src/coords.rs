@@ -0,0 +1,251 @@
+use crate::{IHex, LayoutType};
+
+/// Which rows (pointy layouts) or columns (flat layouts) are shoved by half a hex.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum OffsetType {
+    /// odd rows shoved right, pointy layouts
+    OddR,
+    /// even rows shoved right, pointy layouts
+    EvenR,
+    /// odd columns shoved down, flat layouts
+    OddQ,
+    /// even columns shoved down, flat layouts
+    EvenQ,
+}
+
+impl OffsetType {
+    pub fn layout_type(self) -> LayoutType {
+        match self {
+            OffsetType::OddR | OffsetType::EvenR => LayoutType::Pointy,
+            OffsetType::OddQ | OffsetType::EvenQ => LayoutType::Flat,
+        }
+    }
+
+    /// odd-r for pointy and odd-q for flat layouts, the grid `Rect` uses relative to its first hex
+    pub fn odd(t: LayoutType) -> Self {
+        match t {
+            LayoutType::Pointy => OffsetType::OddR,
+            LayoutType::Flat => OffsetType::OddQ,
+        }
+    }
+}
+
+/// Column and row of a hex in an offset grid of the given type.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct OffsetCoord {
+    pub col: i32,
+    pub row: i32,
+}
+
+impl OffsetCoord {
+    pub fn new(col: i32, row: i32) -> Self {
+        OffsetCoord { col, row }
+    }
+
+    pub fn from_hex(hex: IHex, t: OffsetType) -> Self {
+        let IHex(q, r) = hex;
+        // `& 1` is the parity for negative numbers too
+        match t {
+            OffsetType::OddR => Self::new(q + (r - (r & 1)) / 2, r),
+            OffsetType::EvenR => Self::new(q + (r + (r & 1)) / 2, r),
+            OffsetType::OddQ => Self::new(q, r + (q - (q & 1)) / 2),
+            OffsetType::EvenQ => Self::new(q, r + (q + (q & 1)) / 2),
+        }
+    }
+
+    pub fn to_hex(self, t: OffsetType) -> IHex {
+        let OffsetCoord { col, row } = self;
+        match t {
+            OffsetType::OddR => IHex(col - (row - (row & 1)) / 2, row),
+            OffsetType::EvenR => IHex(col - (row + (row & 1)) / 2, row),
+            OffsetType::OddQ => IHex(col, row - (col - (col & 1)) / 2),
+            OffsetType::EvenQ => IHex(col, row - (col + (col & 1)) / 2),
+        }
+    }
+}
+
+/// Which axis of a doubled grid steps by two between neighbors.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum DoubledType {
+    /// doubled columns, pointy layouts
+    Width,
+    /// doubled rows, flat layouts
+    Height,
+}
+
+/// Doubled coordinates, `col + row` is always even.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct DoubledCoord {
+    pub col: i32,
+    pub row: i32,
+}
+
+impl DoubledCoord {
+    pub fn new(col: i32, row: i32) -> Self {
+        DoubledCoord { col, row }
+    }
+
+    pub fn from_hex(hex: IHex, t: DoubledType) -> Self {
+        let IHex(q, r) = hex;
+        match t {
+            DoubledType::Width => Self::new(2 * q + r, r),
+            DoubledType::Height => Self::new(q, 2 * r + q),
+        }
+    }
+
+    /// None when `col + row` is odd, such coordinates lie between hexes
+    pub fn to_hex(self, t: DoubledType) -> Option<IHex> {
+        let DoubledCoord { col, row } = self;
+        if (col + row) & 1 != 0 {
+            return None;
+        }
+        match t {
+            DoubledType::Width => Some(IHex((col - row) / 2, row)),
+            DoubledType::Height => Some(IHex(col, (row - col) / 2)),
+        }
+    }
+}
+
+/// Cube coordinates with the explicit third axis, `q + r + s == 0`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct CubeHex {
+    q: i32,
+    r: i32,
+    s: i32,
+}
+
+impl CubeHex {
+    /// None when the coordinates do not sum up to zero
+    pub fn new(q: i32, r: i32, s: i32) -> Option<Self> {
+        (q + r + s == 0).then_some(CubeHex { q, r, s })
+    }
+
+    pub fn q(&self) -> i32 {
+        self.q
+    }
+
+    pub fn r(&self) -> i32 {
+        self.r
+    }
+
+    pub fn s(&self) -> i32 {
+        self.s
+    }
+}
+
+impl From<IHex> for CubeHex {
+    fn from(hex: IHex) -> Self {
+        CubeHex {
+            q: hex.q(),
+            r: hex.r(),
+            s: hex.s(),
+        }
+    }
+}
+
+impl From<CubeHex> for IHex {
+    fn from(cube: CubeHex) -> Self {
+        IHex(cube.q, cube.r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    const OFFSET_TYPES: [OffsetType; 4] = [
+        OffsetType::OddR,
+        OffsetType::EvenR,
+        OffsetType::OddQ,
+        OffsetType::EvenQ,
+    ];
+
+    #[test]
+    fn offset_round_trip() {
+        for t in OFFSET_TYPES {
+            for hex in IHex(3, -7).spiral(12) {
+                assert_eq!(OffsetCoord::from_hex(hex, t).to_hex(t), hex, "{t:?}");
+            }
+            for col in -9..9 {
+                for row in -9..9 {
+                    let c = OffsetCoord::new(col, row);
+                    assert_eq!(OffsetCoord::from_hex(c.to_hex(t), t), c, "{t:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn offset_shoved_lines() {
+        // the hex below-right of the origin is in the same column when odd rows are shoved
+        let below = IHex::from(HexDir::BotRight);
+        assert_eq!(
+            OffsetCoord::from_hex(below, OffsetType::OddR),
+            OffsetCoord::new(0, 1)
+        );
+        assert_eq!(
+            OffsetCoord::from_hex(below, OffsetType::EvenR),
+            OffsetCoord::new(1, 1)
+        );
+        let right = IHex::from(HexDir::Right);
+        assert_eq!(
+            OffsetCoord::from_hex(right, OffsetType::OddQ),
+            OffsetCoord::new(1, 0)
+        );
+        assert_eq!(
+            OffsetCoord::from_hex(right, OffsetType::EvenQ),
+            OffsetCoord::new(1, 1)
+        );
+        let left = IHex(-1, 0);
+        assert_eq!(
+            OffsetCoord::from_hex(left, OffsetType::OddQ),
+            OffsetCoord::new(-1, -1)
+        );
+        assert_eq!(
+            OffsetCoord::from_hex(left, OffsetType::EvenQ),
+            OffsetCoord::new(-1, 0)
+        );
+        assert_eq!(OffsetType::EvenQ.layout_type(), LayoutType::Flat);
+    }
+
+    #[test]
+    fn rect_offsets() {
+        for t in [LayoutType::Pointy, LayoutType::Flat] {
+            assert_eq!(OffsetType::odd(t).layout_type(), t);
+            let h0 = IHex(-3, 5);
+            let rect = Rect::with_type(h0, 5, 4, t);
+            for (i, hex) in rect.hex_iter().enumerate() {
+                let (col, row) = ((i % 5) as i32, (i / 5) as i32);
+                let off = OffsetCoord::from_hex(hex - h0, OffsetType::odd(t));
+                assert_eq!(off, OffsetCoord::new(col, row));
+            }
+        }
+    }
+
+    #[test]
+    fn doubled_round_trip() {
+        for t in [DoubledType::Width, DoubledType::Height] {
+            for hex in IHex(-2, 5).spiral(10) {
+                let d = DoubledCoord::from_hex(hex, t);
+                assert_eq!((d.col + d.row) % 2, 0);
+                assert_eq!(d.to_hex(t), Some(hex));
+            }
+            assert_eq!(DoubledCoord::new(1, 0).to_hex(t), None);
+        }
+        let right = DoubledCoord::from_hex(IHex::from(HexDir::Right), DoubledType::Width);
+        assert_eq!(right, DoubledCoord::new(2, 0));
+        let below = DoubledCoord::from_hex(IHex::from(HexDir::BotRight), DoubledType::Height);
+        assert_eq!(below, DoubledCoord::new(0, 2));
+    }
+
+    #[test]
+    fn cube_round_trip() {
+        for hex in IHEX_0.spiral(4) {
+            let cube = CubeHex::from(hex);
+            assert_eq!(cube.q() + cube.r() + cube.s(), 0);
+            assert_eq!(IHex::from(cube), hex);
+            assert_eq!(CubeHex::new(hex.q(), hex.r(), hex.s()), Some(cube));
+        }
+        assert_eq!(CubeHex::new(1, 1, 1), None);
+    }
+}
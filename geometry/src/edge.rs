@@ -0,0 +1,104 @@
+use crate::{ALL_DIR, HexDir, HexVertex, IHex};
+
+/// Side shared by two neighboring hexes.
+///
+/// Every edge is stored once, by the hex it is in one of the first three
+/// directions of (bottom-right, right or top-right).
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct HexEdge {
+    hex: IHex,
+    dir: HexDir,
+}
+
+impl HexEdge {
+    /// edge between `hex` and its neighbor in direction `dir`
+    pub fn new(hex: IHex, dir: HexDir) -> Self {
+        if dir.to_ind() < 3 {
+            HexEdge { hex, dir }
+        } else {
+            HexEdge {
+                hex: hex + dir,
+                dir: dir.opposite(),
+            }
+        }
+    }
+
+    /// hex owning the edge
+    pub fn hex(&self) -> IHex {
+        self.hex
+    }
+
+    /// direction from the owning hex to the other one
+    pub fn dir(&self) -> HexDir {
+        self.dir
+    }
+
+    /// the owning hex and its neighbor across the edge
+    pub fn hexes(&self) -> [IHex; 2] {
+        [self.hex, self.hex + self.dir]
+    }
+
+    /// end points of the edge
+    pub fn vertices(&self) -> [HexVertex; 2] {
+        [
+            HexVertex::new(self.hex, self.dir.rotate(-1)),
+            HexVertex::new(self.hex, self.dir),
+        ]
+    }
+}
+
+impl IHex {
+    pub fn edge(self, dir: HexDir) -> HexEdge {
+        HexEdge::new(self, dir)
+    }
+
+    /// edges towards the neighbors in the order of `ALL_DIR`
+    pub fn edges(self) -> [HexEdge; 6] {
+        ALL_DIR.map(|d| HexEdge::new(self, d))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn canonical() {
+        for hex in IHex(2, -1).spiral(3) {
+            for d in ALL_DIR {
+                let e = hex.edge(d);
+                assert_eq!(e, (hex + d).edge(d.opposite()));
+                assert!(e.dir().to_ind() < 3);
+                assert!(e.hexes().contains(&hex) && e.hexes().contains(&(hex + d)));
+            }
+        }
+    }
+
+    #[test]
+    fn edges_of_hexagon() {
+        // 6 sides for every hex, inner sides are shared by two of them
+        let hexagon = Hexagon::new(IHEX_0, 3);
+        let edges: HashSet<HexEdge> = hexagon.hex_iter().flat_map(|h| h.edges()).collect();
+        let inner = hexagon
+            .hex_iter()
+            .flat_map(|h| h.neighbors())
+            .filter(|n| hexagon.contains(*n))
+            .count();
+        assert_eq!(edges.len(), 6 * hexagon.area() - inner / 2);
+    }
+
+    #[test]
+    fn edge_vertices() {
+        for d in ALL_DIR {
+            let e = IHEX_0.edge(d);
+            let [a, b] = e.vertices();
+            assert_ne!(a, b);
+            // both ends touch the two hexes of the edge
+            for v in [a, b] {
+                assert!(e.hexes().iter().all(|h| v.hexes().contains(h)));
+                assert!(v.edges().contains(&e));
+            }
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{FHex, IHex};
+use std::collections::HashSet;
+
+/// Shift of the line ends so that lines never run exactly along the border of two hexes.
+/// It has to stay well above f32 precision for the line lengths we use.
+const NUDGE: FHex = FHex(1e-4, 2e-4);
+
+/// Line from `from` to `to` with both ends nudged by `nudge`.
+fn nudged_line(from: IHex, to: IHex, nudge: FHex) -> impl Iterator<Item = IHex> {
+    // lerp relative to `from` to keep the coordinates small
+    let end = FHex::from(to - from) + nudge;
+    let dist = from.distance(to);
+    let step = 1.0 / (dist.max(1) as f32);
+    (0..=dist).map(move |i| from + nudge.lerp(end, step * (i as f32)).round())
+}
+
+/// Hexes on the line of sight from `from` to `to`, both ends included.
+/// Same as `IHex::line` but the line never falls between two hexes.
+pub fn sight_line(from: IHex, to: IHex) -> impl Iterator<Item = IHex> {
+    nudged_line(from, to, NUDGE)
+}
+
+/// Checks if `to` can be seen from `from`. Only hexes between the two can block the view,
+/// the line is clear when either of the lines nudged to opposite sides is clear.
+pub fn line_of_sight(from: IHex, to: IHex, opaque: impl Fn(IHex) -> bool) -> bool {
+    let clear = |nudge: FHex| {
+        nudged_line(from, to, nudge)
+            .filter(|&h| h != from && h != to)
+            .all(|h| !opaque(h))
+    };
+    clear(NUDGE) || clear(NUDGE * -1.0)
+}
+
+/// All hexes within `radius` visible from the `center`, opaque hexes themselves are visible.
+pub fn field_of_view(center: IHex, radius: i32, opaque: impl Fn(IHex) -> bool) -> HashSet<IHex> {
+    center
+        .spiral(radius)
+        .filter(|&h| line_of_sight(center, h, &opaque))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn sight_line_connected() {
+        let ends = [
+            IHex(5, 0),
+            IHex(2, 2),
+            IHex(-4, 2),
+            IHex(0, -3),
+            IHex(30, -11),
+        ];
+        for to in ends {
+            let line = sight_line(IHex(1, 1), to + IHex(1, 1)).collect::<Vec<IHex>>();
+            assert_eq!(line.len() as i32, to.length() + 1);
+            assert_eq!(line[0], IHex(1, 1));
+            assert_eq!(line[line.len() - 1], to + IHex(1, 1));
+            for w in line.windows(2) {
+                assert_eq!(w[0].distance(w[1]), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn sight_line_between_hexes() {
+        // the exact line runs along the border of (1, 0) and (1, -1)
+        let line = sight_line(IHex(0, 0), IHex(2, -1)).collect::<Vec<IHex>>();
+        assert_eq!(line.len(), 3);
+        assert!(line[1] == IHex(1, 0) || line[1] == IHex(1, -1));
+        // either of the two hexes alone does not block the view
+        assert!(line_of_sight(IHex(0, 0), IHex(2, -1), |h| h == IHex(1, 0)));
+        assert!(line_of_sight(IHex(0, 0), IHex(2, -1), |h| h == IHex(1, -1)));
+        let both = |h| h == IHex(1, 0) || h == IHex(1, -1);
+        assert!(!line_of_sight(IHex(0, 0), IHex(2, -1), both));
+    }
+
+    #[test]
+    fn wall_blocks_sight() {
+        let wall = Line::new(IHex(2, -3), IHex(2, 3));
+        let opaque = |h| wall.contains(h);
+        assert!(line_of_sight(IHex(0, 0), IHex(2, 0), opaque));
+        assert!(!line_of_sight(IHex(0, 0), IHex(4, 0), opaque));
+        assert!(line_of_sight(IHex(0, 0), IHex(-4, 0), opaque));
+    }
+
+    #[test]
+    fn fov_open_field() {
+        let visible = field_of_view(IHex(3, 3), 4, |_| false);
+        assert_eq!(visible.len(), Hexagon::new(IHex(3, 3), 4).area());
+    }
+
+    #[test]
+    fn fov_behind_pillar() {
+        let pillar = IHex(2, 0);
+        let visible = field_of_view(IHex(0, 0), 5, |h| h == pillar);
+        assert!(visible.contains(&pillar));
+        assert!(!visible.contains(&IHex(4, 0)));
+        assert!(!visible.contains(&IHex(5, 0)));
+        assert!(visible.contains(&IHex(4, -1)));
+        assert!(visible.contains(&IHex(0, 5)));
+        assert!(visible.iter().all(|h| h.length() <= 5));
+    }
+}
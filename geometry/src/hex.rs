@@ -2,7 +2,7 @@ use std::ops::{Add, Mul, Sub};
 
 // https://www.redblobgames.com/grids/hexagons/
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub struct IHex(pub i32, pub i32);
 
 #[derive(Copy, Clone, PartialEq, Debug)]
@@ -35,6 +35,15 @@ impl HexDir {
             BotLeft => 5,
         }
     }
+
+    /// rotate by `n` steps of 60°, positive is counterclockwise
+    pub fn rotate(self, n: i32) -> HexDir {
+        HexDir::from((self.to_ind() as i32 + n).rem_euclid(6) as usize)
+    }
+
+    pub fn opposite(self) -> HexDir {
+        self.rotate(3)
+    }
 }
 
 impl From<usize> for HexDir {
@@ -110,6 +119,62 @@ impl IHex {
         let hex = self;
         ALL_DIR.iter().map(move |&n| hex + IHex::from(n))
     }
+
+    /// rotate around `center` by `steps` of 60° counterclockwise
+    pub fn rotate_left(self, center: IHex, steps: i32) -> IHex {
+        let mut off = self - center;
+        for _ in 0..steps.rem_euclid(6) {
+            off = IHex(-off.s(), -off.q());
+        }
+        center + off
+    }
+
+    /// rotate around `center` by `steps` of 60° clockwise
+    pub fn rotate_right(self, center: IHex, steps: i32) -> IHex {
+        self.rotate_left(center, -steps)
+    }
+
+    /// reflect across the q axis going through `center`
+    pub fn reflect_q(self, center: IHex) -> IHex {
+        let off = self - center;
+        center + IHex(off.q(), off.s())
+    }
+
+    /// reflect across the r axis going through `center`
+    pub fn reflect_r(self, center: IHex) -> IHex {
+        let off = self - center;
+        center + IHex(off.s(), off.r())
+    }
+
+    /// reflect across the s axis going through `center`
+    pub fn reflect_s(self, center: IHex) -> IHex {
+        let off = self - center;
+        center + IHex(off.r(), off.q())
+    }
+
+    /// hexes exactly `radius` steps away, walked counterclockwise from the bottom-left corner;
+    /// ring of radius 0 is the hex itself
+    pub fn ring(self, radius: i32) -> impl Iterator<Item = IHex> {
+        let center = self;
+        let len = match radius {
+            ..0 => 0,
+            0 => 1,
+            _ => 6 * radius,
+        };
+        let n = radius.max(1);
+        (0..len).map(move |i| {
+            let side = (i / n) as usize;
+            let corner = IHex::from(HexDir::from((side + 5) % 6)) * radius;
+            let step = IHex::from(HexDir::from((side + 1) % 6)) * (i % n);
+            center + corner + step
+        })
+    }
+
+    /// hexes within `radius` steps, ring by ring starting from the hex itself
+    pub fn spiral(self, radius: i32) -> impl Iterator<Item = IHex> {
+        let center = self;
+        (0..=radius).flat_map(move |k| center.ring(k))
+    }
 }
 
 impl Add for IHex {
@@ -157,6 +222,10 @@ impl FHex {
         -self.0 - self.1
     }
 
+    pub fn lerp(self, other: FHex, t: f32) -> FHex {
+        FHex(lerp(self.q(), other.q(), t), lerp(self.r(), other.r(), t))
+    }
+
     pub fn round(&self) -> IHex {
         let mut q = self.q().round();
         let mut r = self.r().round();
@@ -247,6 +316,84 @@ mod test {
         assert_eq!(5, IHex(-3, 1).distance(IHex(2, -1)));
     }
 
+    #[test]
+    fn dir_rotate() {
+        assert_eq!(BotRight.rotate(1), Right);
+        assert_eq!(BotRight.rotate(-1), BotLeft);
+        assert_eq!(Left.rotate(8), BotRight);
+        assert_eq!(TopRight.opposite(), BotLeft);
+        for d in ALL_DIR {
+            assert_eq!(d.opposite().opposite(), d);
+            assert_eq!(IHex::from(d) + IHex::from(d.opposite()), IHEX_0);
+        }
+    }
+
+    #[test]
+    fn hex_rotate() {
+        assert_eq!(IHex(1, 0).rotate_left(IHEX_0, 1), IHex(1, -1));
+        assert_eq!(IHex(1, 0).rotate_right(IHEX_0, 1), IHex(0, 1));
+        assert_eq!(IHex(3, 1).rotate_left(IHex(1, 1), 3), IHex(-1, 1));
+        let center = IHex(2, -1);
+        let hex = IHex(4, 3);
+        for steps in -7..7 {
+            let rotated = hex.rotate_left(center, steps);
+            assert_eq!(rotated.distance(center), hex.distance(center));
+            assert_eq!(rotated.rotate_right(center, steps), hex);
+        }
+        assert_eq!(hex.rotate_left(center, 6), hex);
+        for d in ALL_DIR {
+            assert_eq!(IHex::from(d).rotate_left(IHEX_0, 1), IHex::from(d.rotate(1)));
+        }
+    }
+
+    #[test]
+    fn hex_reflect() {
+        let center = IHex(1, 1);
+        let hex = IHex(3, 2);
+        assert_eq!(hex.reflect_q(center), IHex(3, -2));
+        assert_eq!(hex.reflect_r(center), IHex(-2, 2));
+        assert_eq!(hex.reflect_s(center), IHex(2, 3));
+        for h in [hex.reflect_q(center), hex.reflect_r(center), hex.reflect_s(center)] {
+            assert_eq!(h.distance(center), hex.distance(center));
+        }
+        assert_eq!(hex.reflect_q(center).reflect_q(center), hex);
+        assert_eq!(hex.reflect_r(center).reflect_r(center), hex);
+        assert_eq!(hex.reflect_s(center).reflect_s(center), hex);
+    }
+
+    #[test]
+    fn hex_ring() {
+        let want = vec![
+            IHex(-1, 1),
+            IHex(0, 1),
+            IHex(1, 0),
+            IHex(1, -1),
+            IHex(0, -1),
+            IHex(-1, 0),
+        ];
+        assert_eq!(IHEX_0.ring(1).collect::<Vec<IHex>>(), want);
+        assert_eq!(IHex(2, 3).ring(0).collect::<Vec<IHex>>(), vec![IHex(2, 3)]);
+        assert_eq!(IHEX_0.ring(-1).count(), 0);
+        let ring = IHex(2, 3).ring(3).collect::<Vec<IHex>>();
+        assert_eq!(ring.len(), 18);
+        assert_eq!(ring[0], IHex(2, 3) + IHex(-3, 3));
+        for (i, h) in ring.iter().enumerate() {
+            assert_eq!(h.distance(IHex(2, 3)), 3);
+            assert_eq!(h.distance(ring[(i + 1) % ring.len()]), 1);
+        }
+    }
+
+    #[test]
+    fn hex_spiral() {
+        let spiral = IHex(-1, 2).spiral(3).collect::<Vec<IHex>>();
+        assert_eq!(spiral.len(), 37);
+        assert_eq!(spiral[0], IHex(-1, 2));
+        assert_eq!(spiral[1], IHex(-1, 2) + IHex(-1, 1));
+        for (i, h) in spiral.iter().enumerate() {
+            assert!(!spiral[..i].contains(h));
+        }
+    }
+
     #[test]
     fn hex_line() {
         let want = vec![IHex(-2, -1), IHex(-2, 0), IHex(-1, 0), IHex(-1, 1)];
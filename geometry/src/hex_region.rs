@@ -0,0 +1,543 @@
+use crate::{HexDir, IHex, OffsetCoord, OffsetType, Rect, Shape};
+
+/// Largest number of hexes in the bounds of a [`HexRegion`], 128 MiB of bits.
+pub const MAX_REGION_AREA: usize = 1 << 30;
+
+/// Set of hexes stored as one bit per hex of a bounding rectangle.
+///
+/// The rectangle grows when a hex outside of it is inserted. Its first row is
+/// kept even so that its rows and columns are the odd-r offset coordinates of
+/// the hexes, which makes growing a matter of copying the bits into a larger one.
+///
+/// Memory follows the area of the bounds, not the number of hexes: a few hexes
+/// far apart cost as much as the whole rectangle between them. Growing the
+/// bounds past [`MAX_REGION_AREA`] hexes panics.
+#[derive(Clone, Debug)]
+pub struct HexRegion {
+    bounds: Rect,
+    bits: Vec<u64>,
+    len: usize,
+}
+
+impl Default for HexRegion {
+    fn default() -> Self {
+        Self::with_bounds(Rect::new(IHex(0, 0), 0, 0))
+    }
+}
+
+impl HexRegion {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn with_bounds(bounds: Rect) -> Self {
+        HexRegion {
+            bounds,
+            bits: vec![0; bounds.area().div_ceil(64)],
+            len: 0,
+        }
+    }
+
+    /// rectangle the bits are laid over
+    pub fn bounds(&self) -> &Rect {
+        &self.bounds
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.bits.fill(0);
+        self.len = 0;
+    }
+
+    /// add the hex, returns false if it was already there
+    pub fn insert(&mut self, hex: IHex) -> bool {
+        let i = match self.bounds.ind_by_hex(hex) {
+            Some(i) => i,
+            None => {
+                self.grow(hex);
+                self.bounds.ind_by_hex(hex).unwrap()
+            }
+        };
+        let added = !self.bit(i);
+        if added {
+            self.bits[i / 64] |= 1 << (i % 64);
+            self.len += 1;
+        }
+        added
+    }
+
+    /// take the hex out, returns false if it was not there
+    pub fn remove(&mut self, hex: IHex) -> bool {
+        match self.bounds.ind_by_hex(hex) {
+            Some(i) if self.bit(i) => {
+                self.bits[i / 64] &= !(1 << (i % 64));
+                self.len -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// add the hexes of the other region
+    pub fn union_with(&mut self, other: &HexRegion) {
+        let Some(b) = other.extent().filter(|_| !other.is_empty()) else {
+            return;
+        };
+        let a = self.extent();
+        let extent = match a {
+            Some(a) => (a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3)),
+            None => b,
+        };
+        if Some(extent) != a {
+            self.resize(extent);
+        }
+        self.combine(other, b, |a, b| a | b);
+    }
+
+    /// keep only the hexes that are in the other region too
+    pub fn intersect_with(&mut self, other: &HexRegion) {
+        if let Some(a) = self.extent() {
+            self.combine(other, a, |a, b| a & b);
+        }
+    }
+
+    /// take out the hexes of the other region
+    pub fn difference_with(&mut self, other: &HexRegion) {
+        if let (Some(a), Some(b)) = (self.extent(), other.extent()) {
+            let overlap = (a.0.max(b.0), a.1.max(b.1), a.2.min(b.2), a.3.min(b.3));
+            if overlap.0 <= overlap.2 && overlap.1 <= overlap.3 {
+                self.combine(other, overlap, |a, b| a & !b);
+            }
+        }
+    }
+
+    pub fn union(&self, other: &HexRegion) -> HexRegion {
+        let mut region = self.clone();
+        region.union_with(other);
+        region
+    }
+
+    pub fn intersection(&self, other: &HexRegion) -> HexRegion {
+        let mut region = self.clone();
+        region.intersect_with(other);
+        region
+    }
+
+    pub fn difference(&self, other: &HexRegion) -> HexRegion {
+        let mut region = self.clone();
+        region.difference_with(other);
+        region
+    }
+
+    /// the region together with the ring of hexes around it
+    pub fn dilate(&self) -> HexRegion {
+        let mut region = self.clone();
+        for hex in self.hex_iter() {
+            for n in hex.neighbors() {
+                region.insert(n);
+            }
+        }
+        region
+    }
+
+    /// the region without the hexes on its border
+    pub fn erode(&self) -> HexRegion {
+        let mut region = self.clone();
+        region.retain(|h| h.neighbors().all(|n| self.contains(n)));
+        region
+    }
+
+    /// keep only the hexes the predicate holds for
+    pub fn retain(&mut self, mut keep: impl FnMut(IHex) -> bool) {
+        let bounds = self.bounds;
+        for w in 0..self.bits.len() {
+            let mut word = self.bits[w];
+            while word != 0 {
+                let b = word.trailing_zeros() as usize;
+                word &= word - 1;
+                if !keep(bounds.hex_by_ind(w * 64 + b).unwrap()) {
+                    self.bits[w] &= !(1 << b);
+                    self.len -= 1;
+                }
+            }
+        }
+    }
+
+    fn bit(&self, i: usize) -> bool {
+        self.bits[i / 64] & (1 << (i % 64)) != 0
+    }
+
+    fn count(&mut self) {
+        self.len = self.bits.iter().map(|w| w.count_ones() as usize).sum();
+    }
+
+    /// bit index of the odd-r column and row, which must lie in the bounds
+    fn index(&self, col: i32, row: i32) -> usize {
+        let first = OffsetCoord::from_hex(self.bounds.first(), OffsetType::OddR);
+        (row - first.row) as usize * self.bounds.width() as usize + (col - first.col) as usize
+    }
+
+    /// `n` bits from index `i` on, 0 < n <= 64
+    fn read(&self, i: usize, n: usize) -> u64 {
+        let (w, b) = (i / 64, i % 64);
+        let mut word = self.bits[w] >> b;
+        if b + n > 64 {
+            word |= self.bits[w + 1] << (64 - b);
+        }
+        word & (u64::MAX >> (64 - n))
+    }
+
+    /// overwrite `n` bits from index `i` on, 0 < n <= 64
+    fn write(&mut self, i: usize, n: usize, word: u64) {
+        let (w, b) = (i / 64, i % 64);
+        let mask = u64::MAX >> (64 - n);
+        let word = word & mask;
+        self.bits[w] = self.bits[w] & !(mask << b) | word << b;
+        if b + n > 64 {
+            let s = 64 - b;
+            self.bits[w + 1] = self.bits[w + 1] & !(mask >> s) | word >> s;
+        }
+    }
+
+    /// bits of `n` columns from `col` on in the odd-r row, 0 outside of the bounds
+    fn span(&self, col: i32, row: i32, n: usize) -> u64 {
+        let Some((c0, r0, c1, r1)) = self.extent() else {
+            return 0;
+        };
+        let last = col + n as i32 - 1;
+        if row < r0 || row > r1 || last < c0 || col > c1 {
+            return 0;
+        }
+        let (from, to) = (col.max(c0), last.min(c1));
+        self.read(self.index(from, row), (to - from + 1) as usize) << (from - col)
+    }
+
+    /// combine the words of both regions row by row over an extent inside of the bounds
+    fn combine(
+        &mut self,
+        other: &HexRegion,
+        (c0, r0, c1, r1): (i32, i32, i32, i32),
+        op: impl Fn(u64, u64) -> u64,
+    ) {
+        for row in r0..=r1 {
+            let mut col = c0;
+            while col <= c1 {
+                let n = ((c1 - col + 1) as usize).min(64);
+                let i = self.index(col, row);
+                let word = op(self.read(i, n), other.span(col, row, n));
+                self.write(i, n, word);
+                col += n as i32;
+            }
+        }
+        self.count();
+    }
+
+    /// indices of the set bits in increasing order
+    fn indices(&self) -> impl Iterator<Item = usize> {
+        self.bits.iter().enumerate().flat_map(|(w, &word)| {
+            let mut word = word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let b = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(w * 64 + b)
+            })
+        })
+    }
+
+    /// first and last odd-r columns and rows of the bounds, None when they are empty
+    fn extent(&self) -> Option<(i32, i32, i32, i32)> {
+        if self.bounds.area() == 0 {
+            return None;
+        }
+        let first = OffsetCoord::from_hex(self.bounds.first(), OffsetType::OddR);
+        let (w, h) = (self.bounds.width(), self.bounds.height());
+        Some((first.col, first.row, first.col + w - 1, first.row + h - 1))
+    }
+
+    /// enlarge the bounds to take the hex, with room for more in that direction
+    fn grow(&mut self, hex: IHex) {
+        let c = OffsetCoord::from_hex(hex, OffsetType::OddR);
+        let (mut c0, mut r0, mut c1, mut r1) =
+            self.extent().unwrap_or((c.col, c.row, c.col, c.row));
+        let (dc, dr) = (((c1 - c0 + 1) / 2).max(4), ((r1 - r0 + 1) / 2).max(4));
+        if c.col <= c0 {
+            c0 = c.col.saturating_sub(dc);
+        }
+        if c.col >= c1 {
+            c1 = c.col.saturating_add(dc);
+        }
+        if c.row <= r0 {
+            r0 = c.row.saturating_sub(dr);
+        }
+        if c.row >= r1 {
+            r1 = c.row.saturating_add(dr);
+        }
+        self.resize((c0, r0, c1, r1));
+    }
+
+    /// move the bits into bounds with the given first and last columns and rows
+    fn resize(&mut self, (c0, r0, c1, r1): (i32, i32, i32, i32)) {
+        // an even first row keeps the rows of the rect shifted like the odd-r rows
+        let r0 = r0 - (r0 & 1);
+        let area = (c1 as i64 - c0 as i64 + 1) * (r1 as i64 - r0 as i64 + 1);
+        assert!(
+            area <= MAX_REGION_AREA as i64,
+            "region bounds of {area} hexes exceed {MAX_REGION_AREA}"
+        );
+        let h0 = OffsetCoord::new(c0, r0).to_hex(OffsetType::OddR);
+        let mut region = Self::with_bounds(Rect::new(h0, c1 - c0 + 1, r1 - r0 + 1));
+        for hex in self.hex_iter() {
+            let i = region.bounds.ind_by_hex(hex).unwrap();
+            region.bits[i / 64] |= 1 << (i % 64);
+        }
+        region.len = self.len;
+        *self = region;
+    }
+}
+
+impl Shape for HexRegion {
+    /// middle hex in the order of `hex_iter`
+    fn center(&self) -> IHex {
+        self.hex_by_ind(self.len / 2)
+            .unwrap_or_else(|| self.bounds.center())
+    }
+
+    fn area(&self) -> usize {
+        self.len
+    }
+
+    /// hexes row by row of the odd-r offset coordinates
+    fn hex_iter(&self) -> impl Iterator<Item = IHex> {
+        self.indices().map(|i| self.bounds.hex_by_ind(i).unwrap())
+    }
+
+    fn hex_by_ind(&self, ind: usize) -> Option<IHex> {
+        let mut rest = ind;
+        for (w, &word) in self.bits.iter().enumerate() {
+            let ones = word.count_ones() as usize;
+            if rest < ones {
+                let mut word = word;
+                for _ in 0..rest {
+                    word &= word - 1;
+                }
+                return self
+                    .bounds
+                    .hex_by_ind(w * 64 + word.trailing_zeros() as usize);
+            }
+            rest -= ones;
+        }
+        None
+    }
+
+    fn ind_by_hex(&self, hex: IHex) -> Option<usize> {
+        let i = self.bounds.ind_by_hex(hex).filter(|&i| self.bit(i))?;
+        let before: u32 = self.bits[..i / 64].iter().map(|w| w.count_ones()).sum();
+        let mask = (1u64 << (i % 64)) - 1;
+        Some((before + (self.bits[i / 64] & mask).count_ones()) as usize)
+    }
+
+    fn contains(&self, hex: IHex) -> bool {
+        self.bounds.ind_by_hex(hex).is_some_and(|i| self.bit(i))
+    }
+
+    fn move_to(&self, hex: IHex) -> Self {
+        let off = hex - self.center();
+        self.hex_iter().map(|h| h + off).collect()
+    }
+
+    fn all_neighbors(&self) -> impl Iterator<Item = IHex> {
+        let mut seen = HexRegion::new();
+        self.hex_iter()
+            .flat_map(|h| h.neighbors())
+            .filter(move |&n| !self.contains(n) && seen.insert(n))
+    }
+
+    fn direction_neighbors(&self, d: HexDir) -> impl Iterator<Item = IHex> {
+        self.hex_iter()
+            .map(move |h| h + d)
+            .filter(|&n| !self.contains(n))
+    }
+}
+
+impl Extend<IHex> for HexRegion {
+    fn extend<T: IntoIterator<Item = IHex>>(&mut self, iter: T) {
+        for hex in iter {
+            self.insert(hex);
+        }
+    }
+}
+
+impl FromIterator<IHex> for HexRegion {
+    fn from_iter<T: IntoIterator<Item = IHex>>(iter: T) -> Self {
+        let mut region = HexRegion::new();
+        region.extend(iter);
+        region
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::collections::HashSet;
+
+    /// scattered hexes around the given one, on odd and negative rows too
+    fn scattered(center: IHex, radius: i32, salt: i32) -> Vec<IHex> {
+        center
+            .spiral(radius)
+            .filter(|h| (h.q() * 7 + h.r() * 13 + salt).rem_euclid(5) < 2)
+            .collect()
+    }
+
+    #[test]
+    fn insert_remove_grow() {
+        let mut region = HexRegion::new();
+        let mut set = HashSet::new();
+        assert!(region.is_empty() && !region.contains(IHEX_0));
+        for hex in scattered(IHex(-3, -7), 9, 0) {
+            assert_eq!(region.insert(hex), set.insert(hex));
+        }
+        for hex in scattered(IHex(20, 5), 4, 1) {
+            assert_eq!(region.insert(hex), set.insert(hex));
+        }
+        for hex in scattered(IHex(-3, -7), 6, 3) {
+            assert_eq!(region.remove(hex), set.remove(&hex));
+        }
+        assert!(!region.remove(IHex(100, 100)));
+        assert_eq!(region.len(), set.len());
+        for hex in IHEX_0.spiral(30) {
+            assert_eq!(region.contains(hex), set.contains(&hex), "{hex:?}");
+        }
+        region.clear();
+        assert!(region.is_empty() && region.hex_iter().next().is_none());
+    }
+
+    #[test]
+    fn shape_order() {
+        let region: HexRegion = scattered(IHex(4, -9), 7, 2).into_iter().collect();
+        let hexes: Vec<IHex> = region.hex_iter().collect();
+        assert_eq!(hexes.len(), region.area());
+        for (i, h) in hexes.iter().enumerate() {
+            assert_eq!(region.hex_by_ind(i), Some(*h));
+            assert_eq!(region.ind_by_hex(*h), Some(i));
+        }
+        assert_eq!(region.hex_by_ind(hexes.len()), None);
+        // rows top to bottom, left to right inside of a row
+        for pair in hexes.windows(2) {
+            let (a, b) = (
+                OffsetCoord::from_hex(pair[0], OffsetType::OddR),
+                OffsetCoord::from_hex(pair[1], OffsetType::OddR),
+            );
+            assert!((a.row, a.col) < (b.row, b.col));
+        }
+        let moved = region.move_to(IHex(-11, 6));
+        assert_eq!(moved.center(), IHex(-11, 6));
+        assert_eq!(moved.area(), region.area());
+    }
+
+    #[test]
+    fn set_algebra() {
+        let a: HexRegion = scattered(IHEX_0, 6, 0).into_iter().collect();
+        let b: HexRegion = scattered(IHex(3, 2), 6, 1).into_iter().collect();
+        let (sa, sb): (HashSet<IHex>, HashSet<IHex>) =
+            (a.hex_iter().collect(), b.hex_iter().collect());
+        // the originals have different bounds, the copies share the bounds of the union
+        let mut empty = a.union(&b);
+        empty.clear();
+        let (mut a2, mut b2) = (empty.clone(), empty);
+        a2.extend(a.hex_iter());
+        b2.extend(b.hex_iter());
+        assert_eq!(a2.bounds().first(), b2.bounds().first());
+        assert_ne!(a.bounds().first(), b.bounds().first());
+        let check = |r: HexRegion, want: HashSet<IHex>| {
+            assert_eq!(r.len(), want.len());
+            assert!(r.hex_iter().all(|h| want.contains(&h)));
+        };
+        for (a, b) in [(&a, &b), (&a2, &b2)] {
+            check(a.union(b), sa.union(&sb).copied().collect());
+            check(a.intersection(b), sa.intersection(&sb).copied().collect());
+            check(a.difference(b), sa.difference(&sb).copied().collect());
+            check(b.difference(a), sb.difference(&sa).copied().collect());
+        }
+        check(HexRegion::new().union(&a), sa);
+    }
+
+    #[test]
+    fn shifted_words() {
+        // rows wider than a word, bounds starting at different columns and rows
+        let pick = |rect: Rect, salt: i32| -> Vec<IHex> {
+            rect.hex_iter()
+                .filter(|h| (h.q() * 5 + h.r() * 11 + salt).rem_euclid(3) == 0)
+                .collect()
+        };
+        let a: HexRegion = pick(Rect::new(IHex(-70, -5), 140, 9), 0)
+            .into_iter()
+            .collect();
+        let b: HexRegion = pick(Rect::new(IHex(-3, -2), 150, 7), 1)
+            .into_iter()
+            .collect();
+        let c: HexRegion = pick(Rect::new(IHex(500, 40), 70, 3), 2)
+            .into_iter()
+            .collect();
+        for (a, b) in [(&a, &b), (&b, &a), (&a, &c), (&c, &b)] {
+            let (sa, sb): (HashSet<IHex>, HashSet<IHex>) =
+                (a.hex_iter().collect(), b.hex_iter().collect());
+            for (r, want) in [
+                (a.union(b), sa.union(&sb).copied().collect::<HashSet<_>>()),
+                (a.intersection(b), sa.intersection(&sb).copied().collect()),
+                (a.difference(b), sa.difference(&sb).copied().collect()),
+            ] {
+                assert_eq!(r.len(), want.len());
+                assert_eq!(r.hex_iter().collect::<HashSet<_>>(), want);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "region bounds")]
+    fn bounds_limit() {
+        let mut region = HexRegion::new();
+        region.insert(IHex(1 << 20, 0));
+        region.insert(IHex(0, 1 << 20));
+    }
+
+    #[test]
+    fn dilate_erode() {
+        let point: HexRegion = [IHex(2, -3)].into_iter().collect();
+        let hexagon = Hexagon::new(IHex(2, -3), 2);
+        let dilated = point.dilate().dilate();
+        assert_eq!(dilated.area(), hexagon.area());
+        assert!(hexagon.hex_iter().all(|h| dilated.contains(h)));
+        let eroded = dilated.erode();
+        assert_eq!(eroded.area(), 7);
+        assert_eq!(
+            eroded.erode().hex_iter().collect::<Vec<_>>(),
+            vec![IHex(2, -3)]
+        );
+        assert!(eroded.erode().erode().is_empty());
+    }
+
+    #[test]
+    fn neighbors() {
+        let region: HexRegion = Rect::new(IHex(-1, -1), 4, 3).hex_iter().collect();
+        let ring: Vec<IHex> = region.all_neighbors().collect();
+        let want: HashSet<IHex> = region.dilate().difference(&region).hex_iter().collect();
+        assert_eq!(ring.len(), want.len());
+        assert!(ring.iter().all(|h| want.contains(h)));
+        for d in ALL_DIR {
+            for n in region.direction_neighbors(d) {
+                assert!(!region.contains(n) && region.contains(n - IHex::from(d)));
+            }
+        }
+        assert_eq!(AnyShape::from(region.clone()).area(), region.area());
+    }
+}
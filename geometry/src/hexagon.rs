@@ -0,0 +1,189 @@
+use crate::{HexDir, IHex, Shape};
+
+/// A hexagon of tiles within `radius` steps from the `center`.
+/// Tiles are indexed row by row, top to bottom and left to right.
+#[derive(Clone, Copy, Debug)]
+pub struct Hexagon {
+    center: IHex,
+    radius: i32,
+}
+
+impl Hexagon {
+    pub fn new(center: IHex, radius: i32) -> Self {
+        Hexagon { center, radius }
+    }
+
+    pub fn radius(&self) -> i32 {
+        self.radius
+    }
+
+    /// first q offset in a row
+    #[inline]
+    fn row_start(&self, r: i32) -> i32 {
+        (-self.radius).max(-r - self.radius)
+    }
+
+    /// number of tiles before row `k`, counting rows from the top; valid for the upper half
+    #[inline]
+    fn rows_area(&self, k: i32) -> i32 {
+        k * (self.radius + 1) + k * (k - 1) / 2
+    }
+
+    /// hex offset from the center by index, valid for the upper half of the hexagon
+    fn upper_hex_by_ind(&self, ind: i32) -> IHex {
+        // solve k^2 + (2R + 1)k - 2ind = 0 and correct the rounding error
+        let b = (2 * self.radius + 1) as f64;
+        let mut k = ((-b + (b * b + 8.0 * ind as f64).sqrt()) / 2.0) as i32;
+        while self.rows_area(k) > ind {
+            k -= 1;
+        }
+        while self.rows_area(k + 1) <= ind {
+            k += 1;
+        }
+        let r = k - self.radius;
+        IHex(self.row_start(r) + ind - self.rows_area(k), r)
+    }
+
+    /// index by hex offset from the center, valid for the upper half of the hexagon
+    fn upper_ind_by_hex(&self, off: IHex) -> i32 {
+        self.rows_area(off.r() + self.radius) + off.q() - self.row_start(off.r())
+    }
+}
+
+impl Shape for Hexagon {
+    fn center(&self) -> IHex {
+        self.center
+    }
+
+    fn area(&self) -> usize {
+        if self.radius < 0 {
+            0
+        } else {
+            (3 * self.radius * (self.radius + 1) + 1) as usize
+        }
+    }
+
+    fn hex_iter(&self) -> impl Iterator<Item = IHex> {
+        let hexagon = *self;
+        (-self.radius..=self.radius).flat_map(move |r| {
+            let q0 = hexagon.row_start(r);
+            let q1 = hexagon.radius.min(-r + hexagon.radius);
+            (q0..=q1).map(move |q| hexagon.center + IHex(q, r))
+        })
+    }
+
+    fn hex_by_ind(&self, ind: usize) -> Option<IHex> {
+        let area = self.area();
+        if ind >= area {
+            None
+        } else if ind * 2 < area {
+            Some(self.center + self.upper_hex_by_ind(ind as i32))
+        } else {
+            // the order is symmetric around the center
+            let off = self.upper_hex_by_ind((area - 1 - ind) as i32);
+            Some(self.center - off)
+        }
+    }
+
+    fn ind_by_hex(&self, hex: IHex) -> Option<usize> {
+        let off = hex - self.center;
+        if off.length() > self.radius {
+            return None;
+        }
+        let ind = if off.r() <= 0 {
+            self.upper_ind_by_hex(off)
+        } else {
+            // the order is symmetric around the center
+            let mirror = IHex(-off.q(), -off.r());
+            self.area() as i32 - 1 - self.upper_ind_by_hex(mirror)
+        };
+        Some(ind as usize)
+    }
+
+    fn contains(&self, hex: IHex) -> bool {
+        self.center.distance(hex) <= self.radius
+    }
+
+    fn move_to(&self, hex: IHex) -> Self {
+        Hexagon::new(hex, self.radius)
+    }
+
+    fn all_neighbors(&self) -> impl Iterator<Item = IHex> {
+        self.center.ring(self.radius.max(0) + 1)
+    }
+
+    fn direction_neighbors(&self, d: HexDir) -> impl Iterator<Item = IHex> {
+        let hexagon = *self;
+        self.all_neighbors()
+            .filter(move |&n| hexagon.contains(n - IHex::from(d)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hexagon::Hexagon;
+    use crate::{ALL_DIR, IHex, Shape};
+
+    #[test]
+    fn area() {
+        assert_eq!(Hexagon::new(IHex(0, 0), 0).area(), 1);
+        assert_eq!(Hexagon::new(IHex(0, 0), 1).area(), 7);
+        assert_eq!(Hexagon::new(IHex(0, 0), 3).area(), 37);
+    }
+
+    #[test]
+    fn hex_by_ind() {
+        let h = Hexagon::new(IHex(0, 0), 1);
+        let want = vec![
+            IHex(0, -1),
+            IHex(1, -1),
+            IHex(-1, 0),
+            IHex(0, 0),
+            IHex(1, 0),
+            IHex(-1, 1),
+            IHex(0, 1),
+        ];
+        assert_eq!(h.hex_iter().collect::<Vec<IHex>>(), want);
+    }
+
+    #[test]
+    fn ind_round_trip() {
+        for radius in 0..12 {
+            let h = Hexagon::new(IHex(3, -2), radius);
+            let hexes = h.hex_iter().collect::<Vec<IHex>>();
+            assert_eq!(hexes.len(), h.area());
+            for (i, hex) in hexes.iter().enumerate() {
+                assert_eq!(h.hex_by_ind(i), Some(*hex));
+                assert_eq!(h.ind_by_hex(*hex), Some(i));
+                assert!(h.contains(*hex));
+            }
+            assert_eq!(h.hex_by_ind(h.area()), None);
+        }
+    }
+
+    #[test]
+    fn all_neighbors() {
+        for radius in 0..4 {
+            let h = Hexagon::new(IHex(1, 1), radius);
+            let got = h.all_neighbors().collect::<Vec<IHex>>();
+            assert_eq!(got.len(), 6 * (radius as usize + 1));
+            for (i, n) in got.iter().enumerate() {
+                assert_eq!(n.distance(IHex(1, 1)), radius + 1);
+                assert!(!got[..i].contains(n));
+            }
+        }
+    }
+
+    #[test]
+    fn direction_neighbors() {
+        let h = Hexagon::new(IHex(0, 0), 2);
+        for d in ALL_DIR {
+            let got = h.direction_neighbors(d).collect::<Vec<IHex>>();
+            assert_eq!(got.len(), 5);
+            for n in got {
+                assert!(!h.contains(n));
+                assert!(h.contains(n - IHex::from(d)));
+            }
+        }
+    }
+}
@@ -0,0 +1,397 @@
+use super::*;
+use std::fmt::Debug;
+use std::iter::once;
+
+pub const SQRT_3: f32 = 1.7320509;
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Orientation {
+    f0: f32,
+    f1: f32,
+    f2: f32,
+    f3: f32,
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    b3: f32,
+    start_angle: f32, // in multiples of 60°
+}
+
+impl Debug for Orientation {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.layout_type() {
+            LayoutType::Flat => f.write_str("flat"),
+            LayoutType::Pointy => f.write_str("pointy"),
+        }
+    }
+}
+
+pub const ORIENTATION_FLAT: Orientation = Orientation {
+    f0: 3.0 / 2.0,
+    f1: 0.0,
+    f2: SQRT_3 / 2.0,
+    f3: SQRT_3,
+    b0: 2.0 / 3.0,
+    b1: 0.0,
+    b2: -1.0 / 3.0,
+    b3: SQRT_3 / 3.0,
+    start_angle: 0.0,
+};
+
+pub const ORIENTATION_POINTY: Orientation = Orientation {
+    f0: SQRT_3,
+    f1: SQRT_3 / 2.0,
+    f2: 0.0,
+    f3: 3.0 / 2.0,
+    b0: SQRT_3 / 3.0,
+    b1: -1.0 / 3.0,
+    b2: 0.0,
+    b3: 2.0 / 3.0,
+    start_angle: 0.5,
+};
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum LayoutType {
+    Flat = 0,
+    Pointy = 1,
+}
+
+impl LayoutType {
+    pub fn orientation(self) -> Orientation {
+        match self {
+            LayoutType::Flat => ORIENTATION_FLAT,
+            LayoutType::Pointy => ORIENTATION_POINTY,
+        }
+    }
+}
+
+impl Orientation {
+    pub fn layout_type(&self) -> LayoutType {
+        if self.start_angle == 0.0 {
+            LayoutType::Flat
+        } else {
+            LayoutType::Pointy
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Layout {
+    orientation: Orientation,
+    size: Vec2,
+    origin: Vec2,
+    offsets: [Vec2; 6],
+}
+
+impl Layout {
+    pub fn new(orientation: Orientation, size: Vec2, origin: Vec2) -> Self {
+        let offsets = Self::init_offsets(orientation, size);
+        Self {
+            orientation,
+            size,
+            origin,
+            offsets,
+        }
+    }
+
+    fn init_offsets(orientation: Orientation, size: Vec2) -> [Vec2; 6] {
+        let mut offsets = [Vec2(0., 0.); 6];
+        (0..6).for_each(|i| {
+            let angle = 2.0 * core::f32::consts::PI * (orientation.start_angle + i as f32) / 6.0;
+            let (s, c) = angle.sin_cos();
+            offsets[i] = Vec2(size.x() * c, size.y() * s);
+        });
+        offsets
+    }
+
+    pub fn get_orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    pub fn layout_type(&self) -> LayoutType {
+        self.orientation.layout_type()
+    }
+
+    pub fn get_origin(&self) -> Vec2 {
+        self.origin
+    }
+
+    pub fn get_size(&self) -> Vec2 {
+        self.size
+    }
+
+    pub fn set_size(&mut self, size: Vec2) {
+        self.size = size;
+        self.offsets = Self::init_offsets(self.orientation, size);
+    }
+
+    pub fn set_orientation(&mut self, orientation: Orientation) {
+        self.orientation = orientation;
+        self.offsets = Self::init_offsets(orientation, self.size);
+    }
+
+    pub fn set_origin(&mut self, origin: Vec2) {
+        self.origin = origin;
+    }
+
+    pub fn hex_to_pxl(&self, h: IHex) -> Vec2 {
+        self.fhex_to_point(h.into())
+    }
+
+    pub fn fhex_to_point(&self, h: FHex) -> Vec2 {
+        let m = &self.orientation;
+        let x = (m.f0 * h.q() + m.f1 * h.r()) * self.size.x();
+        let y = (m.f2 * h.q() + m.f3 * h.r()) * self.size.y();
+        Vec2(x, y) + self.origin
+    }
+
+    pub fn point_to_fhex(&self, p: Vec2) -> FHex {
+        let l = self;
+        let m = l.orientation;
+        let pt = p - self.origin;
+        let ptl = Vec2(pt.x() / l.size.x(), pt.y() / l.size.y());
+        let q = m.b0 * ptl.x() + m.b1 * ptl.y();
+        let r = m.b2 * ptl.x() + m.b3 * ptl.y();
+        FHex(q, r)
+    }
+
+    pub fn point_to_hex(&self, p: Vec2) -> IHex {
+        self.point_to_fhex(p).round()
+    }
+
+    pub fn hex_centers(&self, hexes: impl Iterator<Item = IHex>) -> impl Iterator<Item = Vec2> {
+        hexes.map(|h| self.hex_to_pxl(h))
+    }
+
+    pub fn grid_corners(&self, c: Vec2) -> [Vec2; 6] {
+        let mut corners = [Vec2(0., 0.); 6];
+        (0..6).for_each(|i| {
+            corners[i] = self.offsets[i] + c;
+        });
+        corners
+    }
+
+    /// direction to the neighbor sharing the edge between corners `i` and `i + 1`
+    pub fn edge_dir(&self, i: usize) -> HexDir {
+        use HexDir::*;
+        const POINTY: [HexDir; 6] = [BotRight, BotLeft, Left, TopLeft, TopRight, Right];
+        const FLAT: [HexDir; 6] = [Right, BotRight, BotLeft, Left, TopLeft, TopRight];
+        match self.layout_type() {
+            LayoutType::Pointy => POINTY[i % 6],
+            LayoutType::Flat => FLAT[i % 6],
+        }
+    }
+
+    /// vertex at corner `i` of `grid_corners`
+    pub fn corner_vertex(&self, hex: IHex, i: usize) -> HexVertex {
+        // corners go the other way around than directions, corner `i` follows edge `i - 1`
+        HexVertex::new(hex, self.edge_dir(i))
+    }
+
+    /// corner shared by the three hexes, their centers are around it at equal distance
+    pub fn vertex_to_pxl(&self, v: HexVertex) -> Vec2 {
+        let [a, b, c] = v.hexes();
+        self.fhex_to_point(FHex::from(a + b + c) * (1. / 3.))
+    }
+
+    /// middle of the side shared by the two hexes
+    pub fn edge_to_pxl(&self, e: HexEdge) -> Vec2 {
+        let [a, b] = e.hexes();
+        self.fhex_to_point(FHex::from(a + b) * 0.5)
+    }
+
+    /// end points of the side shared by the two hexes
+    pub fn edge_ends(&self, e: HexEdge) -> [Vec2; 2] {
+        e.vertices().map(|v| self.vertex_to_pxl(v))
+    }
+
+    /// edge closest to the point if it is at most `tolerance` pixels away
+    pub fn point_to_edge(&self, p: Vec2, tolerance: f32) -> Option<HexEdge> {
+        // hexes are convex, no edge is closer than the sides of the hex under the point
+        self.point_to_hex(p)
+            .edges()
+            .into_iter()
+            .map(|e| {
+                let [a, b] = self.edge_ends(e);
+                (e, segment_distance(p, a, b))
+            })
+            .filter(|&(_, d)| d <= tolerance)
+            .min_by(|(_, d1), (_, d2)| d1.total_cmp(d2))
+            .map(|(e, _)| e)
+    }
+
+    /// vertex closest to the point if it is at most `tolerance` pixels away
+    pub fn point_to_vertex(&self, p: Vec2, tolerance: f32) -> Option<HexVertex> {
+        // corners of the neighbors too, stretched layouts can put them closer
+        let hex = self.point_to_hex(p);
+        once(hex)
+            .chain(hex.neighbors())
+            .flat_map(|h| h.vertices())
+            .map(|v| (v, (self.vertex_to_pxl(v) - p).length()))
+            .filter(|&(_, d)| d <= tolerance)
+            .min_by(|(_, d1), (_, d2)| d1.total_cmp(d2))
+            .map(|(v, _)| v)
+    }
+}
+
+/// distance from `p` to the segment between `a` and `b`
+fn segment_distance(p: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let (ab, ap) = (b - a, p - a);
+    let len2 = ab.x() * ab.x() + ab.y() * ab.y();
+    let t = if len2 > 0. {
+        ((ap.x() * ab.x() + ap.y() * ab.y()) / len2).clamp(0., 1.)
+    } else {
+        0.
+    };
+    (ap - ab * t).length()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn round_trip(orientation: Orientation) {
+        let layout = Layout::new(orientation, Vec2(12., 9.), Vec2(40., 25.));
+        let rect = Rect::with_type(IHex(-5, -5), 10, 10, orientation.layout_type());
+        for hex in rect.hex_iter() {
+            let center = layout.hex_to_pxl(hex);
+            assert_eq!(layout.point_to_hex(center), hex);
+            for corner in layout.grid_corners(center) {
+                // nudge slightly from the corner towards the center
+                let p = Vec2(
+                    corner.x() + (center.x() - corner.x()) * 0.1,
+                    corner.y() + (center.y() - corner.y()) * 0.1,
+                );
+                assert_eq!(layout.point_to_hex(p), hex);
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip_pointy() {
+        round_trip(ORIENTATION_POINTY);
+    }
+
+    #[test]
+    fn round_trip_flat() {
+        round_trip(ORIENTATION_FLAT);
+    }
+
+    #[test]
+    fn flat_hex_to_pxl() {
+        let layout = Layout::new(ORIENTATION_FLAT, Vec2(10., 10.), Vec2(0., 0.));
+        let p = layout.hex_to_pxl(IHex(2, 0));
+        assert!((p.x() - 30.).abs() < 1e-4);
+        assert!((p.y() - 10. * 3f32.sqrt()).abs() < 1e-4);
+        let p = layout.hex_to_pxl(IHex(0, 1));
+        assert!(p.x().abs() < 1e-4);
+        assert!((p.y() - 10. * 3f32.sqrt()).abs() < 1e-4);
+    }
+
+    #[test]
+    fn grid_corners_orientation() {
+        let origin = Vec2(0., 0.);
+        let size = Vec2(10., 10.);
+        let flat = Layout::new(ORIENTATION_FLAT, size, origin).grid_corners(origin);
+        assert!((flat[0].x() - 10.).abs() < 1e-4 && flat[0].y().abs() < 1e-4);
+        let pointy = Layout::new(ORIENTATION_POINTY, size, origin).grid_corners(origin);
+        assert!(pointy[1].x().abs() < 1e-4 && (pointy[1].y() - 10.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn edge_dir_matches_neighbor() {
+        for orientation in [ORIENTATION_POINTY, ORIENTATION_FLAT] {
+            let layout = Layout::new(orientation, Vec2(10., 7.), Vec2(3., 4.));
+            let center = layout.hex_to_pxl(IHEX_0);
+            let corners = layout.grid_corners(center);
+            for i in 0..6 {
+                let (a, b) = (corners[i], corners[(i + 1) % 6]);
+                // mirror the center over the edge middle to land in the neighbor
+                let mid = Vec2((a.x() + b.x()) / 2., (a.y() + b.y()) / 2.);
+                let p = Vec2(2. * mid.x() - center.x(), 2. * mid.y() - center.y());
+                let neighbor = IHEX_0 + layout.edge_dir(i);
+                assert_eq!(layout.point_to_hex(p), neighbor, "{orientation:?} {i}");
+            }
+        }
+    }
+
+    #[test]
+    fn edges_and_vertices_match_corners() {
+        let near = |a: Vec2, b: Vec2| (a - b).length() < 1e-3;
+        for orientation in [ORIENTATION_POINTY, ORIENTATION_FLAT] {
+            let layout = Layout::new(orientation, Vec2(13., 6.), Vec2(-7., 2.));
+            for hex in IHex(1, -2).spiral(2) {
+                let corners = layout.grid_corners(layout.hex_to_pxl(hex));
+                for i in 0..6 {
+                    let v = layout.corner_vertex(hex, i);
+                    assert!(v.hexes().contains(&hex));
+                    assert!(
+                        near(layout.vertex_to_pxl(v), corners[i]),
+                        "{orientation:?} {i}"
+                    );
+                    let (a, b) = (corners[i], corners[(i + 1) % 6]);
+                    let e = hex.edge(layout.edge_dir(i));
+                    let ends = layout.edge_ends(e);
+                    assert!(ends.iter().any(|p| near(*p, a)) && ends.iter().any(|p| near(*p, b)));
+                    assert!(near(layout.edge_to_pxl(e), (a + b) * 0.5));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pick_edges() {
+        for orientation in [ORIENTATION_POINTY, ORIENTATION_FLAT] {
+            // stretched hexes, pixel distances differ along the axes
+            let layout = Layout::new(orientation, Vec2(24., 9.), Vec2(5., -3.));
+            for hex in IHex(2, 1).spiral(2) {
+                let center = layout.hex_to_pxl(hex);
+                assert_eq!(layout.point_to_edge(center, 2.), None);
+                for e in hex.edges() {
+                    let [a, b] = layout.edge_ends(e);
+                    let d = b - a;
+                    let normal = Vec2(-d.y(), d.x()) * (1. / d.length());
+                    let mid = layout.edge_to_pxl(e);
+                    assert_eq!(layout.point_to_edge(mid, 0.01), Some(e));
+                    for side in [1., -1.] {
+                        let p = mid + normal * (side * 1.8);
+                        assert_eq!(layout.point_to_edge(p, 2.), Some(e), "{orientation:?}");
+                        assert_eq!(layout.point_to_edge(p, 1.7), None, "{orientation:?}");
+                    }
+                    // close to an end the edge still wins over the center
+                    let p = a + (mid - a) * 0.2 + (center - mid) * 0.05;
+                    assert_eq!(layout.point_to_edge(p, 3.), Some(e));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pick_vertices() {
+        for orientation in [ORIENTATION_POINTY, ORIENTATION_FLAT] {
+            let layout = Layout::new(orientation, Vec2(8., 21.), Vec2(-40., 11.));
+            for hex in IHex(-1, 3).spiral(2) {
+                let center = layout.hex_to_pxl(hex);
+                assert_eq!(layout.point_to_vertex(center, 3.), None);
+                for (i, corner) in layout.grid_corners(center).into_iter().enumerate() {
+                    let v = layout.corner_vertex(hex, i);
+                    assert_eq!(layout.point_to_vertex(corner, 0.01), Some(v));
+                    for dir in [Vec2(1., 0.), Vec2(0., -1.), Vec2(-0.6, 0.8)] {
+                        let p = corner + dir * 2.5;
+                        assert_eq!(layout.point_to_vertex(p, 2.6), Some(v), "{orientation:?}");
+                        assert_eq!(layout.point_to_vertex(p, 2.4), None, "{orientation:?}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn orientation_layout_type() {
+        assert_eq!(ORIENTATION_FLAT.layout_type(), LayoutType::Flat);
+        assert_eq!(ORIENTATION_POINTY.layout_type(), LayoutType::Pointy);
+        let flat = LayoutType::Flat.orientation();
+        assert_eq!(flat.layout_type(), LayoutType::Flat);
+    }
+}
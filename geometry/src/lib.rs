@@ -0,0 +1,42 @@
+//! Hex grid math: coordinates, shapes and pixel layouts, without any windowing dependency.
+//! Enable the `sdl2` feature for conversions to SDL points.
+
+mod hex;
+mod shape;
+mod point;
+mod layout;
+mod rect;
+mod line;
+mod hexagon;
+mod rotated;
+mod coords;
+mod vec2;
+mod edge;
+mod vertex;
+mod any_shape;
+mod map_shape;
+mod region;
+mod hex_region;
+mod chunked;
+mod pathfinding;
+mod fov;
+
+pub use hex::*;
+pub use shape::*;
+pub use point::*;
+pub use layout::*;
+pub use rect::*;
+pub use line::*;
+pub use hexagon::*;
+pub use rotated::*;
+pub use coords::*;
+pub use vec2::*;
+pub use edge::*;
+pub use vertex::*;
+pub use any_shape::*;
+pub use map_shape::*;
+pub use region::*;
+pub use hex_region::*;
+pub use chunked::*;
+pub use pathfinding::*;
+pub use fov::*;
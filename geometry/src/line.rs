@@ -0,0 +1,225 @@
+use crate::{HexDir, IHex, Shape};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Line {
+    h0: IHex,
+    h1: IHex,
+    t: LineType,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum LineType {
+    G, // generic
+    Q, // q-axis
+    R, // r-axis
+    S, // s-axis
+    V, // vertical for pointy-top
+}
+
+use LineType::*;
+
+impl Line {
+    pub fn new(h0: IHex, h1: IHex) -> Self {
+        let diff = h1 - h0;
+        let t;
+        if diff.q() == 0 {
+            t = Q;
+        } else if diff.r() == 0 {
+            t = R;
+        } else if diff.s() == 0 {
+            t = S;
+        } else if 2 * diff.q() + diff.r() == 0 {
+            t = V;
+        } else {
+            t = G;
+        }
+        Line { h0, h1, t }
+    }
+
+    pub fn start(&self) -> IHex {
+        self.h0
+    }
+
+    pub fn end(&self) -> IHex {
+        self.h1
+    }
+}
+
+impl Shape for Line {
+    fn center(&self) -> IHex {
+        self.hex_by_ind(self.area() / 2).unwrap_or(self.h0)
+    }
+
+    fn area(&self) -> usize {
+        self.h0.distance(self.h1) as usize + 1
+    }
+
+    fn hex_iter(&self) -> impl Iterator<Item = IHex> {
+        let line = *self;
+        (0..self.area()).filter_map(move |i| line.hex_by_ind(i))
+    }
+
+    fn hex_by_ind(&self, ind: usize) -> Option<IHex> {
+        if ind >= self.area() {
+            None
+        } else {
+            let diff = self.h1 - self.h0;
+            let ind = ind as i32;
+            match self.t {
+                G => Some(self.h0.nlerp(self.h1, ind as usize)),
+                Q => Some(IHex(self.h0.q(), self.h0.r() + ind * diff.r().signum())),
+                R => Some(IHex(self.h0.q() + ind * diff.q().signum(), self.h0.r())),
+                S => {
+                    let q = self.h0.q() + ind * diff.q().signum();
+                    Some(IHex(q, -q - self.h0.s()))
+                }
+                V => {
+                    // zigzag down (or up) the column: every second step shifts q
+                    let q = (ind / 2) * diff.q().signum();
+                    let r = ind * diff.r().signum();
+                    Some(IHex(self.h0.q() + q, self.h0.r() + r))
+                }
+            }
+        }
+    }
+
+    fn ind_by_hex(&self, hex: IHex) -> Option<usize> {
+        // every hex of the line is a step further away from h0
+        let ind = self.h0.distance(hex) as usize;
+        if self.hex_by_ind(ind) == Some(hex) {
+            Some(ind)
+        } else {
+            None
+        }
+    }
+
+    fn contains(&self, hex: IHex) -> bool {
+        self.ind_by_hex(hex).is_some()
+    }
+
+    fn move_to(&self, hex: IHex) -> Self {
+        let off = hex - self.center();
+        Line {
+            h0: self.h0 + off,
+            h1: self.h1 + off,
+            t: self.t,
+        }
+    }
+
+    fn all_neighbors(&self) -> impl Iterator<Item = IHex> {
+        let line = *self;
+        self.hex_iter().enumerate().flat_map(move |(i, hex)| {
+            hex.neighbors().filter(move |&n| {
+                // a neighbor shared with one of two previous hexes is already emitted
+                !line.contains(n)
+                    && (i.saturating_sub(2)..i)
+                        .filter_map(|j| line.hex_by_ind(j))
+                        .all(|prev| prev.distance(n) > 1)
+            })
+        })
+    }
+
+    fn direction_neighbors(&self, d: HexDir) -> impl Iterator<Item = IHex> {
+        let line = *self;
+        self.hex_iter()
+            .map(move |hex| hex + d)
+            .filter(move |&n| !line.contains(n))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::line::{Line, LineType};
+    use crate::{ALL_DIR, HexDir, IHex, Shape};
+
+    #[test]
+    fn line_center() {
+        let l = Line::new(IHex(1, 0), IHex(5, 0));
+        assert_eq!(l.area(), 5);
+        assert_eq!(l.center(), IHex(3, 0));
+    }
+
+    #[test]
+    fn line_types() {
+        assert_eq!(Line::new(IHex(0, 0), IHex(0, 3)).t, LineType::Q);
+        assert_eq!(Line::new(IHex(0, 0), IHex(3, 0)).t, LineType::R);
+        assert_eq!(Line::new(IHex(0, 0), IHex(3, -3)).t, LineType::S);
+        assert_eq!(Line::new(IHex(0, 0), IHex(-2, 4)).t, LineType::V);
+        assert_eq!(Line::new(IHex(0, 0), IHex(1, 3)).t, LineType::G);
+    }
+
+    #[test]
+    fn line_iter_matches_ind() {
+        let ends = [
+            IHex(0, 3),
+            IHex(-3, 0),
+            IHex(3, -3),
+            IHex(2, -4),
+            IHex(-2, 4),
+            IHex(1, 3),
+        ];
+        for h1 in ends {
+            let l = Line::new(IHex(1, 1), h1 + IHex(1, 1));
+            let hexes = l.hex_iter().collect::<Vec<IHex>>();
+            assert_eq!(hexes.len(), l.area());
+            assert_eq!(hexes.first(), Some(&IHex(1, 1)));
+            assert_eq!(hexes.last(), Some(&(h1 + IHex(1, 1))));
+            for (i, hex) in hexes.iter().enumerate() {
+                assert_eq!(l.hex_by_ind(i), Some(*hex));
+                assert_eq!(l.ind_by_hex(*hex), Some(i));
+                assert!(l.contains(*hex));
+            }
+            for w in hexes.windows(2) {
+                assert_eq!(w[0].distance(w[1]), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn line_vertical() {
+        let l = Line::new(IHex(0, 0), IHex(-2, 4));
+        let want = vec![
+            IHex(0, 0),
+            IHex(0, 1),
+            IHex(-1, 2),
+            IHex(-1, 3),
+            IHex(-2, 4),
+        ];
+        assert_eq!(l.hex_iter().collect::<Vec<IHex>>(), want);
+        assert!(!l.contains(IHex(-1, 1)));
+    }
+
+    #[test]
+    fn line_all_neighbors() {
+        let l = Line::new(IHex(0, 0), IHex(2, 0));
+        let neigh = l.all_neighbors().collect::<Vec<IHex>>();
+        assert_eq!(neigh.len(), 10);
+        for (i, n) in neigh.iter().enumerate() {
+            assert!(!l.contains(*n));
+            assert!(l.hex_iter().any(|h| h.distance(*n) == 1));
+            assert!(!neigh[..i].contains(n));
+        }
+    }
+
+    #[test]
+    fn line_direction_neighbors() {
+        let l = Line::new(IHex(0, 0), IHex(2, 0));
+        let right = l.direction_neighbors(HexDir::Right).collect::<Vec<IHex>>();
+        assert_eq!(right, vec![IHex(3, 0)]);
+        let top = l
+            .direction_neighbors(HexDir::TopLeft)
+            .collect::<Vec<IHex>>();
+        assert_eq!(top, vec![IHex(0, -1), IHex(1, -1), IHex(2, -1)]);
+        for d in ALL_DIR {
+            assert!(l.direction_neighbors(d).all(|n| !l.contains(n)));
+        }
+    }
+
+    #[test]
+    fn line_move_to() {
+        let l = Line::new(IHex(0, 0), IHex(4, 0)).move_to(IHex(0, 5));
+        assert_eq!(l.center(), IHex(0, 5));
+        assert_eq!(l.start(), IHex(-2, 5));
+        assert_eq!(l.end(), IHex(2, 5));
+    }
+}
@@ -0,0 +1,119 @@
+use crate::{AnyShape, HexDir, Hexagon, IHex, Rect, Shape};
+
+/// Shape of a whole map or of a chunk of one, the shapes map files can store.
+#[derive(Clone, Copy, Debug)]
+pub enum MapShape {
+    Rect(Rect),
+    Hexagon(Hexagon),
+}
+
+impl Shape for MapShape {
+    fn center(&self) -> IHex {
+        match self {
+            MapShape::Rect(s) => s.center(),
+            MapShape::Hexagon(s) => s.center(),
+        }
+    }
+
+    fn area(&self) -> usize {
+        match self {
+            MapShape::Rect(s) => s.area(),
+            MapShape::Hexagon(s) => s.area(),
+        }
+    }
+
+    fn hex_iter(&self) -> impl Iterator<Item = IHex> {
+        let iter: Box<dyn Iterator<Item = IHex>> = match self {
+            MapShape::Rect(s) => Box::new(s.hex_iter()),
+            MapShape::Hexagon(s) => Box::new(s.hex_iter()),
+        };
+        iter
+    }
+
+    fn hex_by_ind(&self, ind: usize) -> Option<IHex> {
+        match self {
+            MapShape::Rect(s) => s.hex_by_ind(ind),
+            MapShape::Hexagon(s) => s.hex_by_ind(ind),
+        }
+    }
+
+    fn ind_by_hex(&self, hex: IHex) -> Option<usize> {
+        match self {
+            MapShape::Rect(s) => s.ind_by_hex(hex),
+            MapShape::Hexagon(s) => s.ind_by_hex(hex),
+        }
+    }
+
+    fn contains(&self, hex: IHex) -> bool {
+        match self {
+            MapShape::Rect(s) => s.contains(hex),
+            MapShape::Hexagon(s) => s.contains(hex),
+        }
+    }
+
+    fn move_to(&self, hex: IHex) -> Self {
+        match self {
+            MapShape::Rect(s) => MapShape::Rect(s.move_to(hex)),
+            MapShape::Hexagon(s) => MapShape::Hexagon(s.move_to(hex)),
+        }
+    }
+
+    fn all_neighbors(&self) -> impl Iterator<Item = IHex> {
+        let iter: Box<dyn Iterator<Item = IHex>> = match self {
+            MapShape::Rect(s) => Box::new(s.all_neighbors()),
+            MapShape::Hexagon(s) => Box::new(s.all_neighbors()),
+        };
+        iter
+    }
+
+    fn direction_neighbors(&self, d: HexDir) -> impl Iterator<Item = IHex> {
+        let iter: Box<dyn Iterator<Item = IHex>> = match self {
+            MapShape::Rect(s) => Box::new(s.direction_neighbors(d)),
+            MapShape::Hexagon(s) => Box::new(s.direction_neighbors(d)),
+        };
+        iter
+    }
+}
+
+impl From<Rect> for MapShape {
+    fn from(s: Rect) -> Self {
+        MapShape::Rect(s)
+    }
+}
+
+impl From<Hexagon> for MapShape {
+    fn from(s: Hexagon) -> Self {
+        MapShape::Hexagon(s)
+    }
+}
+
+impl From<MapShape> for AnyShape {
+    fn from(s: MapShape) -> Self {
+        match s {
+            MapShape::Rect(s) => AnyShape::Rect(s),
+            MapShape::Hexagon(s) => AnyShape::Hexagon(s),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn same_as_any_shape() {
+        let shapes: [MapShape; 2] = [
+            Rect::with_type(IHex(1, 2), 4, 3, LayoutType::Flat).into(),
+            Hexagon::new(IHex(-3, 1), 2).into(),
+        ];
+        for s in shapes {
+            let any = AnyShape::from(s);
+            assert_eq!(s.area(), any.area());
+            assert!(s.hex_iter().eq(any.hex_iter()));
+            assert!(s.all_neighbors().eq(any.all_neighbors()));
+            let moved = s.move_to(IHex(10, -3));
+            assert_eq!(moved.center(), IHex(10, -3));
+            assert!(moved.hex_iter().eq(any.move_to(IHex(10, -3)).hex_iter()));
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use crate::{IHex, Shape};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+/// A route between two hexes, both ends included.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Path {
+    pub hexes: Vec<IHex>,
+    pub cost: u32,
+}
+
+/// Costs of reaching hexes from a single start, produced by Dijkstra search.
+#[derive(Clone, Debug)]
+pub struct DistanceMap {
+    start: IHex,
+    costs: HashMap<IHex, u32>,
+    came_from: HashMap<IHex, IHex>,
+}
+
+impl DistanceMap {
+    pub fn start(&self) -> IHex {
+        self.start
+    }
+
+    /// total cost of reaching the hex, None if it was not reached
+    pub fn cost(&self, hex: IHex) -> Option<u32> {
+        self.costs.get(&hex).copied()
+    }
+
+    /// all reached hexes with their costs, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (IHex, u32)> {
+        self.costs.iter().map(|(h, c)| (*h, *c))
+    }
+
+    /// cheapest route from the start to the hex
+    pub fn path_to(&self, hex: IHex) -> Option<Path> {
+        let cost = self.cost(hex)?;
+        Some(Path {
+            hexes: walk_back(&self.came_from, hex),
+            cost,
+        })
+    }
+}
+
+/// Searches over the hex grid with caller supplied movement rules.
+///
+/// `passable` tells if a unit can enter the hex, `cost` is the price of a single step
+/// between two neighbors, steps cheaper than 1 are counted as 1.
+/// The grid is unbounded, every search stops at the given cost budget
+/// so it ends even when the goal is walled in. Costs saturate at `u32::MAX`,
+/// hexes that expensive are never reached.
+pub struct Pathfinder<P, C> {
+    passable: P,
+    cost: C,
+}
+
+impl<P, C> Pathfinder<P, C>
+where
+    P: Fn(IHex) -> bool,
+    C: Fn(IHex, IHex) -> u32,
+{
+    pub fn new(passable: P, cost: C) -> Self {
+        Pathfinder { passable, cost }
+    }
+
+    /// limit searches to hexes of the shape
+    pub fn within<S: Shape>(self, shape: &S) -> Pathfinder<impl Fn(IHex) -> bool, C> {
+        let passable = self.passable;
+        Pathfinder {
+            passable: move |h| shape.contains(h) && passable(h),
+            cost: self.cost,
+        }
+    }
+
+    /// cheapest route from start to goal costing at most `budget`,
+    /// using hex distance as the heuristic
+    pub fn astar(&self, start: IHex, goal: IHex, budget: u32) -> Option<Path> {
+        let budget = budget.min(u32::MAX - 1);
+        if start != goal && !(self.passable)(goal) {
+            return None;
+        }
+        let mut frontier: BinaryHeap<Reverse<(u32, i32, i32)>> = BinaryHeap::new();
+        let mut costs = HashMap::from([(start, 0)]);
+        let mut came_from = HashMap::new();
+        frontier.push(Reverse((0, start.q(), start.r())));
+        while let Some(Reverse((_, q, r))) = frontier.pop() {
+            let current = IHex(q, r);
+            if current == goal {
+                return Some(Path {
+                    hexes: walk_back(&came_from, goal),
+                    cost: costs[&goal],
+                });
+            }
+            let current_cost = costs[&current];
+            for next in current.neighbors().filter(|h| (self.passable)(*h)) {
+                let cost = current_cost.saturating_add(self.step(current, next));
+                if cost <= budget && costs.get(&next).is_none_or(|&c| cost < c) {
+                    costs.insert(next, cost);
+                    came_from.insert(next, current);
+                    let priority = cost.saturating_add(next.distance(goal) as u32);
+                    frontier.push(Reverse((priority, next.q(), next.r())));
+                }
+            }
+        }
+        None
+    }
+
+    /// costs of all hexes reachable from start within the budget
+    pub fn dijkstra(&self, start: IHex, budget: u32) -> DistanceMap {
+        let budget = budget.min(u32::MAX - 1);
+        let mut frontier: BinaryHeap<Reverse<(u32, i32, i32)>> = BinaryHeap::new();
+        let mut costs = HashMap::from([(start, 0)]);
+        let mut came_from = HashMap::new();
+        frontier.push(Reverse((0, start.q(), start.r())));
+        while let Some(Reverse((cost, q, r))) = frontier.pop() {
+            let current = IHex(q, r);
+            if cost > costs[&current] {
+                // stale entry, a cheaper route was found already
+                continue;
+            }
+            for next in current.neighbors().filter(|h| (self.passable)(*h)) {
+                let cost = cost.saturating_add(self.step(current, next));
+                if cost <= budget && costs.get(&next).is_none_or(|&c| cost < c) {
+                    costs.insert(next, cost);
+                    came_from.insert(next, current);
+                    frontier.push(Reverse((cost, next.q(), next.r())));
+                }
+            }
+        }
+        DistanceMap {
+            start,
+            costs,
+            came_from,
+        }
+    }
+
+    /// hexes reachable from start within the budget, cheapest first
+    /// starting with the start itself
+    pub fn reachable(&self, start: IHex, budget: u32) -> Vec<IHex> {
+        let mut reached: Vec<(u32, IHex)> = self
+            .dijkstra(start, budget)
+            .iter()
+            .map(|(h, c)| (c, h))
+            .collect();
+        reached.sort_by_key(|&(c, h)| (c, h.r(), h.q()));
+        reached.into_iter().map(|(_, h)| h).collect()
+    }
+
+    #[inline]
+    fn step(&self, from: IHex, to: IHex) -> u32 {
+        (self.cost)(from, to).max(1)
+    }
+}
+
+fn walk_back(came_from: &HashMap<IHex, IHex>, goal: IHex) -> Vec<IHex> {
+    let mut hexes = vec![goal];
+    let mut current = goal;
+    while let Some(&prev) = came_from.get(&current) {
+        hexes.push(prev);
+        current = prev;
+    }
+    hexes.reverse();
+    hexes
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn astar_open_field() {
+        let pf = Pathfinder::new(|_| true, |_, _| 1);
+        let path = pf.astar(IHex(0, 0), IHex(3, -5), 100).unwrap();
+        assert_eq!(path.cost, 5);
+        assert_eq!(path.hexes.len(), 6);
+        assert_eq!(path.hexes[0], IHex(0, 0));
+        assert_eq!(path.hexes[5], IHex(3, -5));
+        for w in path.hexes.windows(2) {
+            assert_eq!(w[0].distance(w[1]), 1);
+        }
+    }
+
+    #[test]
+    fn astar_around_wall() {
+        let wall = Line::new(IHex(2, -3), IHex(2, 3));
+        let pf = Pathfinder::new(|h| !wall.contains(h), |_, _| 1);
+        let path = pf.astar(IHex(0, 0), IHex(4, 0), 100).unwrap();
+        assert!(path.hexes.iter().all(|h| !wall.contains(*h)));
+        assert_eq!(path.cost, path.hexes.len() as u32 - 1);
+        assert!(path.cost > 4);
+    }
+
+    #[test]
+    fn astar_prefers_cheap_tiles() {
+        let swamp = Hexagon::new(IHex(2, 0), 1);
+        let pf = Pathfinder::new(|_| true, |_, to| if swamp.contains(to) { 5 } else { 1 });
+        let path = pf.astar(IHex(0, 0), IHex(4, 0), 100).unwrap();
+        assert!(path.hexes.iter().all(|h| !swamp.contains(*h)));
+        assert_eq!(path.cost, path.hexes.len() as u32 - 1);
+    }
+
+    #[test]
+    fn astar_unreachable() {
+        let bounds = Rect::new(IHex(0, 0), 4, 4);
+        let walled = |h: IHex| h.distance(IHex(2, 2)) != 1;
+        let pf = Pathfinder::new(walled, |_, _| 1).within(&bounds);
+        assert_eq!(pf.astar(IHex(0, 0), IHex(2, 2), u32::MAX), None);
+        let pf = Pathfinder::new(|_| true, |_, _| 1).within(&bounds);
+        assert_eq!(pf.astar(IHex(0, 0), IHex(10, 0), u32::MAX), None);
+        assert!(pf.astar(IHex(0, 0), IHex(3, 0), u32::MAX).is_some());
+    }
+
+    #[test]
+    fn astar_budget() {
+        // walled in goal on an unbounded grid
+        let walled = |h: IHex| h.distance(IHex(5, 0)) != 1;
+        let pf = Pathfinder::new(walled, |_, _| 1);
+        assert_eq!(pf.astar(IHex(0, 0), IHex(5, 0), 20), None);
+        let pf = Pathfinder::new(|_| true, |_, _| 0);
+        assert_eq!(pf.astar(IHex(0, 0), IHex(5, 0), 4), None);
+        assert_eq!(pf.astar(IHex(0, 0), IHex(5, 0), 5).unwrap().cost, 5);
+        let pf = Pathfinder::new(|_| true, |_, _| u32::MAX);
+        assert_eq!(pf.astar(IHex(0, 0), IHex(1, 0), u32::MAX), None);
+        assert_eq!(pf.dijkstra(IHex(0, 0), u32::MAX).iter().count(), 1);
+    }
+
+    #[test]
+    fn dijkstra_budget() {
+        let pf = Pathfinder::new(|_| true, |_, _| 2);
+        let map = pf.dijkstra(IHex(1, 1), 6);
+        assert_eq!(map.iter().count(), Hexagon::new(IHex(1, 1), 3).area());
+        assert_eq!(map.cost(IHex(1, 1)), Some(0));
+        assert_eq!(map.cost(IHex(4, 1)), Some(6));
+        assert_eq!(map.cost(IHex(5, 1)), None);
+        let path = map.path_to(IHex(1, -2)).unwrap();
+        assert_eq!(path.cost, 6);
+        assert_eq!(path.hexes.len(), 4);
+    }
+
+    #[test]
+    fn dijkstra_within_shape() {
+        let bounds = Rect::new(IHex(0, 0), 5, 5);
+        let pf = Pathfinder::new(|_| true, |_, _| 1).within(&bounds);
+        let map = pf.dijkstra(IHex(0, 0), u32::MAX);
+        assert_eq!(map.iter().count(), bounds.area());
+        assert!(map.iter().all(|(h, _)| bounds.contains(h)));
+    }
+
+    #[test]
+    fn reachable_moves() {
+        let blocked = Hexagon::new(IHex(1, 0), 0);
+        let pf = Pathfinder::new(|h| !blocked.contains(h), |_, _| 1);
+        let reach = pf.reachable(IHex(0, 0), 2);
+        assert_eq!(reach[0], IHex(0, 0));
+        // the hex behind the obstacle needs a detour
+        assert_eq!(reach.len(), Hexagon::new(IHex(0, 0), 2).area() - 2);
+        assert!(!reach.contains(&IHex(1, 0)));
+        assert!(!reach.contains(&IHex(2, 0)));
+        assert!(pf.reachable(IHex(0, 0), 3).contains(&IHex(2, 0)));
+        assert_eq!(pf.reachable(IHex(0, 0), 0), vec![IHex(0, 0)]);
+    }
+
+    #[test]
+    fn reachable_terrain_costs() {
+        let swamp = Hexagon::new(IHex(2, 0), 1);
+        let pf = Pathfinder::new(|_| true, |_, to| if swamp.contains(to) { 3 } else { 1 });
+        let reach = pf.reachable(IHex(0, 0), 2);
+        assert_eq!(reach[0], IHex(0, 0));
+        assert!(!reach.iter().any(|h| swamp.contains(*h)));
+        assert!(pf.reachable(IHex(0, 0), 3).contains(&IHex(1, 0)));
+        assert!(!pf.reachable(IHex(0, 0), 3).contains(&IHex(2, 0)));
+    }
+}
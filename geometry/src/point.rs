@@ -2,6 +2,7 @@ use std::iter::once;
 
 use super::*;
 
+#[derive(Clone, Copy, Debug)]
 pub struct Point(pub IHex);
 
 impl Shape for Point {
@@ -0,0 +1,340 @@
+use crate::{HexDir, IHex, LayoutType, OffsetCoord, OffsetType, Shape};
+
+/// A rectangle of `w` columns and `h` rows starting at `h0`.
+/// Pointy layouts use shifted rows (odd-r), flat layouts use shifted columns (odd-q).
+#[derive(Clone, Copy, Debug)]
+pub struct Rect {
+    h0: IHex,
+    w: i32,
+    h: i32,
+    t: LayoutType,
+}
+
+impl Rect {
+    pub fn new(h0: IHex, w: i32, h: i32) -> Self {
+        Self::with_type(h0, w, h, LayoutType::Pointy)
+    }
+
+    pub fn with_type(h0: IHex, w: i32, h: i32, t: LayoutType) -> Self {
+        Rect { h0, w, h, t }
+    }
+
+    /// smallest rectangle with both hexes in its corners
+    pub fn from_corners(a: IHex, b: IHex, t: LayoutType) -> Self {
+        // start from the hex on the first shifted row (pointy) or column (flat)
+        let first_a = match t {
+            LayoutType::Pointy => a.r() <= b.r(),
+            LayoutType::Flat => a.q() <= b.q(),
+        };
+        let (first, last) = if first_a { (a, b) } else { (b, a) };
+        let OffsetCoord { col, row } = OffsetCoord::from_hex(last - first, OffsetType::odd(t));
+        // moving along the first row or column keeps the shift parity
+        let h0 = first + OffsetCoord::new(col.min(0), row.min(0)).to_hex(OffsetType::odd(t));
+        Rect::with_type(h0, col.abs() + 1, row.abs() + 1, t)
+    }
+
+    pub fn layout_type(&self) -> LayoutType {
+        self.t
+    }
+
+    /// hex in the first column of the first row
+    pub fn first(&self) -> IHex {
+        self.h0
+    }
+
+    /// number of columns
+    pub fn width(&self) -> i32 {
+        self.w
+    }
+
+    /// number of rows
+    pub fn height(&self) -> i32 {
+        self.h
+    }
+}
+
+/// offsets from `h0` are in the `OffsetType::odd` grid, the first row or column is not shoved
+#[inline]
+fn h0_to_hc(h0: IHex, w: i32, h: i32, t: LayoutType) -> IHex {
+    h0 + OffsetCoord::new(w / 2, h / 2).to_hex(OffsetType::odd(t))
+}
+
+#[inline]
+fn hc_to_h0(hc: IHex, w: i32, h: i32, t: LayoutType) -> IHex {
+    hc - OffsetCoord::new(w / 2, h / 2).to_hex(OffsetType::odd(t))
+}
+
+impl Shape for Rect {
+    fn center(&self) -> IHex {
+        h0_to_hc(self.h0, self.w, self.h, self.t)
+    }
+
+    fn area(&self) -> usize {
+        self.w as usize * self.h as usize
+    }
+
+    fn hex_iter(&self) -> impl Iterator<Item = IHex> {
+        RectIter {
+            rect: *self,
+            i: 0,
+        }
+    }
+
+    fn hex_by_ind(&self, ind: usize) -> Option<IHex> {
+        if ind >= self.area() {
+            None
+        } else {
+            let row = ind as i32 / self.w;
+            let col = ind as i32 % self.w;
+            Some(self.h0 + OffsetCoord::new(col, row).to_hex(OffsetType::odd(self.t)))
+        }
+    }
+
+    fn ind_by_hex(&self, hex: IHex) -> Option<usize> {
+        let off = OffsetCoord::from_hex(hex - self.h0, OffsetType::odd(self.t));
+        let (col, row) = (off.col, off.row);
+        if col >= 0 && col < self.w && row >= 0 && row < self.h {
+            Some((col + row * self.w) as usize)
+        } else {
+            None
+        }
+    }
+
+    fn contains(&self, hex: IHex) -> bool {
+        self.ind_by_hex(hex).is_some()
+    }
+
+    fn move_to(&self, hex: IHex) -> Self {
+        let h0 = hc_to_h0(hex, self.w, self.h, self.t);
+        Rect::with_type(h0, self.w, self.h, self.t)
+    }
+
+    fn all_neighbors(&self) -> impl Iterator<Item = IHex> {
+        RectNeighIter::new(*self)
+    }
+
+    fn direction_neighbors(&self, d: HexDir) -> impl Iterator<Item = IHex> {
+        let rect = *self;
+        self.all_neighbors()
+            .filter(move |&n| rect.contains(n - IHex::from(d)))
+    }
+}
+
+/// Outer ring of the rectangle walked clockwise side by side,
+/// starting from the top-left corner.
+struct RectNeighIter {
+    rect: Rect,
+    state: RectNeighSideIter,
+}
+
+impl RectNeighIter {
+    fn new(rect: Rect) -> Self {
+        RectNeighIter {
+            rect,
+            state: RectNeighSideIter::Top { i: 0 },
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum RectNeighSideIter {
+    Top { i: usize },
+    Right { i: usize },
+    Bottom { i: usize },
+    Left { i: usize },
+}
+
+use RectNeighSideIter::*;
+
+impl RectNeighSideIter {
+    fn i(self) -> usize {
+        match self {
+            Top { i } | Right { i } | Bottom { i } | Left { i } => i,
+        }
+    }
+
+    fn advance(self) -> Self {
+        match self {
+            Top { i } => Top { i: i + 1 },
+            Right { i } => Right { i: i + 1 },
+            Bottom { i } => Bottom { i: i + 1 },
+            Left { i } => Left { i: i + 1 },
+        }
+    }
+}
+
+impl Rect {
+    /// hex at the current position of the perimeter side, None when side is exhausted
+    fn side_hex(&self, side: RectNeighSideIter) -> Option<IHex> {
+        if self.w <= 0 || self.h <= 0 {
+            return None;
+        }
+        let (q0, r0, w, h) = (self.h0.q(), self.h0.r(), self.w, self.h);
+        let i = side.i() as i32;
+        let (len, hex) = match (self.t, side) {
+            (LayoutType::Pointy, Top { .. }) => (w + 1, IHex(q0 + i, r0 - 1)),
+            (LayoutType::Pointy, Right { .. }) => (h, IHex(q0 - i / 2 + w, r0 + i)),
+            (LayoutType::Pointy, Bottom { .. }) => {
+                let q = q0 - (h - 1) / 2;
+                (w + 1, IHex(q + w - 1 - i, r0 + h))
+            }
+            (LayoutType::Pointy, Left { .. }) => {
+                let row = h - 1 - i;
+                (h, IHex(q0 - row / 2 - 1, r0 + row))
+            }
+            (LayoutType::Flat, Top { .. }) => (w, IHex(q0 + i, r0 - i / 2 - 1)),
+            (LayoutType::Flat, Right { .. }) => {
+                let r = r0 - (w - 1) / 2;
+                (h + 1, IHex(q0 + w, r - 1 + i))
+            }
+            (LayoutType::Flat, Bottom { .. }) => {
+                let col = w - 1 - i;
+                (w, IHex(q0 + col, r0 - col / 2 + h))
+            }
+            (LayoutType::Flat, Left { .. }) => (h + 1, IHex(q0 - 1, r0 + h - i)),
+        };
+        if i < len { Some(hex) } else { None }
+    }
+}
+
+impl Iterator for RectNeighIter {
+    type Item = IHex;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(h) = self.rect.side_hex(self.state) {
+                self.state = self.state.advance();
+                return Some(h);
+            }
+            self.state = match self.state {
+                Top { .. } => Right { i: 0 },
+                Right { .. } => Bottom { i: 0 },
+                Bottom { .. } => Left { i: 0 },
+                Left { .. } => return None,
+            };
+        }
+    }
+}
+
+struct RectIter {
+    rect: Rect,
+    i: usize,
+}
+
+impl Iterator for RectIter {
+    type Item = IHex;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.rect.hex_by_ind(self.i) {
+            Some(h) => {
+                self.i += 1;
+                Some(h)
+            }
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rect::Rect;
+    use crate::{ALL_DIR, IHex, LayoutType, Shape};
+
+    #[test]
+    fn hex_by_ind() {
+        let r = Rect::new(IHex(0, 0), 3, 3);
+        assert_eq!(r.hex_by_ind(0), Some(IHex(0, 0)));
+        assert_eq!(r.hex_by_ind(5), Some(IHex(2, 1)));
+        assert_eq!(r.hex_by_ind(8), Some(IHex(1,2)));
+    }
+
+    #[test]
+    fn hex_by_ind_flat() {
+        let r = Rect::with_type(IHex(0, 0), 3, 3, LayoutType::Flat);
+        assert_eq!(r.hex_by_ind(0), Some(IHex(0, 0)));
+        assert_eq!(r.hex_by_ind(5), Some(IHex(2, 0)));
+        assert_eq!(r.hex_by_ind(7), Some(IHex(1, 2)));
+    }
+
+    #[test]
+    fn ind_by_hex_round_trip() {
+        for t in [LayoutType::Pointy, LayoutType::Flat] {
+            let r = Rect::with_type(IHex(-3, 2), 5, 4, t);
+            for i in 0..r.area() {
+                let hex = r.hex_by_ind(i).unwrap();
+                assert_eq!(r.ind_by_hex(hex), Some(i));
+            }
+            assert_eq!(r.hex_iter().count(), r.area());
+        }
+    }
+
+    #[test]
+    fn center_is_inside() {
+        for t in [LayoutType::Pointy, LayoutType::Flat] {
+            let r = Rect::with_type(IHex(0, 0), 20, 20, t);
+            assert_eq!(r.ind_by_hex(r.center()), Some(10 * 20 + 10));
+            let moved = r.move_to(IHex(7, -3));
+            assert_eq!(moved.center(), IHex(7, -3));
+        }
+    }
+
+    #[test]
+    fn from_corners() {
+        for t in [LayoutType::Pointy, LayoutType::Flat] {
+            let a = IHex(1, -2);
+            for b in a.spiral(5) {
+                for (x, y) in [(a, b), (b, a)] {
+                    let r = Rect::from_corners(x, y, t);
+                    let (w, h) = (r.w as usize, r.h as usize);
+                    let corner = |col, row| r.hex_by_ind(row * w + col).unwrap();
+                    let corners = [
+                        corner(0, 0),
+                        corner(w - 1, 0),
+                        corner(0, h - 1),
+                        corner(w - 1, h - 1),
+                    ];
+                    assert!(corners.contains(&a), "{t:?} {a:?} {b:?}");
+                    assert!(corners.contains(&b), "{t:?} {a:?} {b:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn all_neighbors() {
+        for t in [LayoutType::Pointy, LayoutType::Flat] {
+            for (w, h) in [(1, 1), (3, 3), (4, 5), (5, 2)] {
+                let r = Rect::with_type(IHex(-2, 3), w, h, t);
+                let got = r.all_neighbors().collect::<Vec<IHex>>();
+                assert_eq!(got.len(), 2 * (w + h + 1) as usize);
+                for (i, n) in got.iter().enumerate() {
+                    assert!(!r.contains(*n));
+                    assert!(r.hex_iter().any(|h| h.distance(*n) == 1));
+                    assert!(!got[..i].contains(n));
+                }
+                // consecutive hexes of the ring touch each other
+                for (a, b) in got.iter().zip(got.iter().cycle().skip(1)) {
+                    assert_eq!(a.distance(*b), 1);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn direction_neighbors() {
+        for t in [LayoutType::Pointy, LayoutType::Flat] {
+            let r = Rect::with_type(IHex(0, 0), 4, 3, t);
+            for d in ALL_DIR {
+                let got = r.direction_neighbors(d).collect::<Vec<IHex>>();
+                let mut want = r
+                    .hex_iter()
+                    .map(|h| h + d)
+                    .filter(|h| !r.contains(*h))
+                    .collect::<Vec<IHex>>();
+                assert_eq!(got.len(), want.len());
+                want.retain(|h| !got.contains(h));
+                assert!(want.is_empty());
+            }
+        }
+    }
+}
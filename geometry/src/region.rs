@@ -0,0 +1,188 @@
+use crate::{AnyShape, HexDir, IHex, Shape};
+use std::collections::HashSet;
+
+/// Set operation on two shapes, the operands may be regions themselves.
+///
+/// Hexes are listed in the order of the first shape followed by the new hexes of
+/// the second one for unions. Queries walk the operands, so `area`, `hex_by_ind`
+/// and `ind_by_hex` take time proportional to their size.
+#[derive(Clone, Debug)]
+pub enum Region {
+    Union(AnyShape, AnyShape),
+    Intersection(AnyShape, AnyShape),
+    Difference(AnyShape, AnyShape),
+}
+
+impl Region {
+    pub fn union(a: impl Into<AnyShape>, b: impl Into<AnyShape>) -> Self {
+        Region::Union(a.into(), b.into())
+    }
+
+    pub fn intersection(a: impl Into<AnyShape>, b: impl Into<AnyShape>) -> Self {
+        Region::Intersection(a.into(), b.into())
+    }
+
+    /// hexes of `a` that are not in `b`
+    pub fn difference(a: impl Into<AnyShape>, b: impl Into<AnyShape>) -> Self {
+        Region::Difference(a.into(), b.into())
+    }
+
+    pub fn operands(&self) -> (&AnyShape, &AnyShape) {
+        match self {
+            Region::Union(a, b) | Region::Intersection(a, b) | Region::Difference(a, b) => (a, b),
+        }
+    }
+}
+
+impl Shape for Region {
+    /// center of the first shape
+    fn center(&self) -> IHex {
+        self.operands().0.center()
+    }
+
+    fn area(&self) -> usize {
+        self.hex_iter().count()
+    }
+
+    fn hex_iter(&self) -> impl Iterator<Item = IHex> {
+        let iter: Box<dyn Iterator<Item = IHex> + '_> = match self {
+            Region::Union(a, b) => {
+                Box::new(a.hex_iter().chain(b.hex_iter().filter(|h| !a.contains(*h))))
+            }
+            Region::Intersection(a, b) => Box::new(a.hex_iter().filter(|h| b.contains(*h))),
+            Region::Difference(a, b) => Box::new(a.hex_iter().filter(|h| !b.contains(*h))),
+        };
+        iter
+    }
+
+    fn hex_by_ind(&self, ind: usize) -> Option<IHex> {
+        self.hex_iter().nth(ind)
+    }
+
+    fn ind_by_hex(&self, hex: IHex) -> Option<usize> {
+        if self.contains(hex) {
+            self.hex_iter().position(|h| h == hex)
+        } else {
+            None
+        }
+    }
+
+    fn contains(&self, hex: IHex) -> bool {
+        match self {
+            Region::Union(a, b) => a.contains(hex) || b.contains(hex),
+            Region::Intersection(a, b) => a.contains(hex) && b.contains(hex),
+            Region::Difference(a, b) => a.contains(hex) && !b.contains(hex),
+        }
+    }
+
+    /// moves both shapes keeping their relative position
+    fn move_to(&self, hex: IHex) -> Self {
+        let off = hex - self.center();
+        let (a, b) = self.operands();
+        let (a, b) = (a.move_to(a.center() + off), b.move_to(b.center() + off));
+        match self {
+            Region::Union(..) => Region::Union(a, b),
+            Region::Intersection(..) => Region::Intersection(a, b),
+            Region::Difference(..) => Region::Difference(a, b),
+        }
+    }
+
+    fn all_neighbors(&self) -> impl Iterator<Item = IHex> {
+        let mut seen = HashSet::new();
+        self.hex_iter()
+            .flat_map(|h| h.neighbors())
+            .filter(move |n| !self.contains(*n) && seen.insert(*n))
+    }
+
+    fn direction_neighbors(&self, d: HexDir) -> impl Iterator<Item = IHex> {
+        self.hex_iter()
+            .map(move |h| h + d)
+            .filter(|n| !self.contains(*n))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::collections::HashSet;
+
+    /// ring of radius 2 and 3 with a line through it and without the top-right part
+    fn sample() -> Region {
+        let ring = Region::difference(Hexagon::new(IHEX_0, 3), Hexagon::new(IHEX_0, 1));
+        let cross = Region::union(ring, Line::new(IHex(-4, 0), IHex(4, 0)));
+        Region::difference(cross, Rect::new(IHex(1, -4), 5, 3))
+    }
+
+    #[test]
+    fn set_operations() {
+        let (big, small) = (Hexagon::new(IHEX_0, 3), Hexagon::new(IHex(2, 0), 2));
+        let union = Region::union(big, small);
+        let inter = Region::intersection(big, small);
+        let diff = Region::difference(big, small);
+        for h in IHEX_0.spiral(6) {
+            assert_eq!(union.contains(h), big.contains(h) || small.contains(h));
+            assert_eq!(inter.contains(h), big.contains(h) && small.contains(h));
+            assert_eq!(diff.contains(h), big.contains(h) && !small.contains(h));
+        }
+        assert_eq!(union.area() + inter.area(), big.area() + small.area());
+        assert_eq!(diff.area() + inter.area(), big.area());
+        // the first shape keeps its order
+        assert!(union.hex_iter().take(big.area()).eq(big.hex_iter()));
+    }
+
+    #[test]
+    fn stable_order() {
+        let region = sample();
+        let hexes: Vec<IHex> = region.hex_iter().collect();
+        assert_eq!(hexes, region.hex_iter().collect::<Vec<IHex>>());
+        assert_eq!(hexes.iter().collect::<HashSet<_>>().len(), hexes.len());
+        assert_eq!(region.area(), hexes.len());
+        for (i, h) in hexes.iter().enumerate() {
+            assert!(region.contains(*h));
+            assert_eq!(region.hex_by_ind(i), Some(*h));
+            assert_eq!(region.ind_by_hex(*h), Some(i));
+        }
+        assert_eq!(region.ind_by_hex(IHex(0, 1)), None);
+        assert!(region.contains(IHex(-1, 0)));
+    }
+
+    #[test]
+    fn neighbors_and_perimeter() {
+        let region = sample();
+        let neighbors: Vec<IHex> = region.all_neighbors().collect();
+        let want: HashSet<IHex> = region
+            .hex_iter()
+            .flat_map(|h| h.neighbors())
+            .filter(|n| !region.contains(*n))
+            .collect();
+        assert_eq!(neighbors.len(), want.len());
+        assert!(neighbors.iter().all(|n| want.contains(n)));
+        for d in ALL_DIR {
+            for n in region.direction_neighbors(d) {
+                assert!(!region.contains(n) && region.contains(n - IHex::from(d)));
+            }
+        }
+        let boundary: Vec<HexEdge> = region.boundary().collect();
+        assert_eq!(
+            boundary.iter().collect::<HashSet<_>>().len(),
+            boundary.len()
+        );
+        assert_eq!(region.perimeter(), boundary.len());
+        // a hexagon with a hole has an outer and an inner border
+        let ring = Region::difference(Hexagon::new(IHEX_0, 2), Point(IHEX_0));
+        assert_eq!(ring.perimeter(), 6 * 5 + 6);
+    }
+
+    #[test]
+    fn move_and_nest() {
+        let region = sample();
+        let moved = region.move_to(IHex(7, -2));
+        assert_eq!(moved.center(), IHex(7, -2));
+        let off = IHex(7, -2) - region.center();
+        assert!(moved.hex_iter().eq(region.hex_iter().map(|h| h + off)));
+        // regions mix with other shapes and rotate like them
+        let shapes: Vec<AnyShape> = vec![region.clone().into(), region.rotate(1).into()];
+        assert_eq!(shapes[0].area(), shapes[1].area());
+        assert!(shapes[1].contains(IHex(-1, 0).rotate_left(region.center(), 1)));
+    }
+}
@@ -0,0 +1,124 @@
+use crate::{HexDir, IHex, Shape};
+
+/// A shape rotated around its center by `steps` of 60° counterclockwise.
+/// Keeps the tile order of the original shape.
+#[derive(Clone, Copy, Debug)]
+pub struct Rotated<S: Shape> {
+    shape: S,
+    steps: i32,
+}
+
+impl<S: Shape> Rotated<S> {
+    pub fn new(shape: S, steps: i32) -> Self {
+        Rotated {
+            shape,
+            steps: steps.rem_euclid(6),
+        }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.shape
+    }
+
+    pub fn steps(&self) -> i32 {
+        self.steps
+    }
+}
+
+impl<S: Shape> Shape for Rotated<S> {
+    fn center(&self) -> IHex {
+        self.shape.center()
+    }
+
+    fn area(&self) -> usize {
+        self.shape.area()
+    }
+
+    fn hex_iter(&self) -> impl Iterator<Item = IHex> {
+        let (center, steps) = (self.center(), self.steps);
+        self.shape
+            .hex_iter()
+            .map(move |h| h.rotate_left(center, steps))
+    }
+
+    fn hex_by_ind(&self, ind: usize) -> Option<IHex> {
+        let (center, steps) = (self.center(), self.steps);
+        self.shape
+            .hex_by_ind(ind)
+            .map(|h| h.rotate_left(center, steps))
+    }
+
+    fn ind_by_hex(&self, hex: IHex) -> Option<usize> {
+        self.shape
+            .ind_by_hex(hex.rotate_right(self.center(), self.steps))
+    }
+
+    fn contains(&self, hex: IHex) -> bool {
+        self.shape
+            .contains(hex.rotate_right(self.center(), self.steps))
+    }
+
+    fn move_to(&self, hex: IHex) -> Self {
+        Rotated {
+            shape: self.shape.move_to(hex),
+            steps: self.steps,
+        }
+    }
+
+    fn all_neighbors(&self) -> impl Iterator<Item = IHex> {
+        let (center, steps) = (self.center(), self.steps);
+        self.shape
+            .all_neighbors()
+            .map(move |h| h.rotate_left(center, steps))
+    }
+
+    fn direction_neighbors(&self, d: HexDir) -> impl Iterator<Item = IHex> {
+        let (center, steps) = (self.center(), self.steps);
+        self.shape
+            .direction_neighbors(d.rotate(-steps))
+            .map(move |h| h.rotate_left(center, steps))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ALL_DIR, IHex, Line, Point, Rect, Shape};
+
+    #[test]
+    fn rotate_point() {
+        let p = Point(IHex(3, 4)).rotate(2);
+        assert_eq!(p.hex_iter().collect::<Vec<IHex>>(), vec![IHex(3, 4)]);
+    }
+
+    #[test]
+    fn rotate_line() {
+        let l = Line::new(IHex(-2, 0), IHex(2, 0)).rotate(1);
+        let want = Line::new(IHex(-2, 2), IHex(2, -2));
+        assert_eq!(
+            l.hex_iter().collect::<Vec<IHex>>(),
+            want.hex_iter().collect::<Vec<IHex>>()
+        );
+    }
+
+    #[test]
+    fn rotate_rect() {
+        let r = Rect::new(IHex(1, -2), 4, 3);
+        for steps in 0..6 {
+            let rotated = r.rotate(steps);
+            assert_eq!(rotated.center(), r.center());
+            for (i, h) in rotated.hex_iter().enumerate() {
+                assert_eq!(rotated.hex_by_ind(i), Some(h));
+                assert_eq!(rotated.ind_by_hex(h), Some(i));
+                assert!(rotated.contains(h));
+            }
+            for d in ALL_DIR {
+                for n in rotated.direction_neighbors(d) {
+                    assert!(!rotated.contains(n));
+                    assert!(rotated.contains(n - IHex::from(d)));
+                }
+            }
+        }
+        let full = r.rotate(6).hex_iter().collect::<Vec<IHex>>();
+        assert_eq!(full, r.hex_iter().collect::<Vec<IHex>>());
+    }
+}
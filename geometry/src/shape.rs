@@ -32,4 +32,27 @@ pub trait Shape {
 
     /// iterator of neighboring hexes in given direction
     fn direction_neighbors(&self, d: HexDir) -> impl Iterator<Item = IHex>;
+
+    /// sides between the shape and the outside, each once, in the order of `hex_iter`
+    fn boundary(&self) -> impl Iterator<Item = HexEdge> {
+        self.hex_iter().flat_map(move |h| {
+            ALL_DIR
+                .into_iter()
+                .filter(move |&d| !self.contains(h + d))
+                .map(move |d| h.edge(d))
+        })
+    }
+
+    /// number of sides between the shape and the outside
+    fn perimeter(&self) -> usize {
+        self.boundary().count()
+    }
+
+    /// copy of the shape rotated around its center by `steps` of 60° counterclockwise
+    fn rotate(&self, steps: i32) -> Rotated<Self>
+    where
+        Self: Sized + Clone,
+    {
+        Rotated::new(self.clone(), steps)
+    }
 }
@@ -0,0 +1,113 @@
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+/// Point or offset in pixel space.
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct Vec2(pub f32, pub f32);
+
+impl Vec2 {
+    pub fn x(&self) -> f32 {
+        self.0
+    }
+
+    pub fn y(&self) -> f32 {
+        self.1
+    }
+
+    pub fn length(&self) -> f32 {
+        self.0.hypot(self.1)
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Vec2;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Vec2(self.0 + rhs.0, self.1 + rhs.1)
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Vec2;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Vec2(self.0 - rhs.0, self.1 - rhs.1)
+    }
+}
+
+impl AddAssign for Vec2 {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Vec2 {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul<f32> for Vec2 {
+    type Output = Vec2;
+
+    fn mul(self, rhs: f32) -> Self::Output {
+        Vec2(self.0 * rhs, self.1 * rhs)
+    }
+}
+
+impl Div<f32> for Vec2 {
+    type Output = Vec2;
+
+    fn div(self, rhs: f32) -> Self::Output {
+        Vec2(self.0 / rhs, self.1 / rhs)
+    }
+}
+
+impl Neg for Vec2 {
+    type Output = Vec2;
+
+    fn neg(self) -> Self::Output {
+        Vec2(-self.0, -self.1)
+    }
+}
+
+#[cfg(feature = "sdl2")]
+impl From<Vec2> for sdl2::rect::FPoint {
+    fn from(v: Vec2) -> Self {
+        sdl2::rect::FPoint::new(v.0, v.1)
+    }
+}
+
+#[cfg(feature = "sdl2")]
+impl From<sdl2::rect::FPoint> for Vec2 {
+    fn from(p: sdl2::rect::FPoint) -> Self {
+        Vec2(p.x(), p.y())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Vec2;
+
+    #[test]
+    fn ops() {
+        let mut v = Vec2(3., 4.);
+        assert_eq!(v.length(), 5.);
+        assert_eq!(v + Vec2(1., -1.), Vec2(4., 3.));
+        assert_eq!(v - Vec2(1., -1.), Vec2(2., 5.));
+        assert_eq!(v * 2., Vec2(6., 8.));
+        assert_eq!(v / 2., Vec2(1.5, 2.));
+        assert_eq!(-v, Vec2(-3., -4.));
+        v += Vec2(1., 1.);
+        v -= Vec2(0., 2.);
+        assert_eq!(v, Vec2(4., 3.));
+    }
+
+    #[cfg(feature = "sdl2")]
+    #[test]
+    fn fpoint_conversion() {
+        use sdl2::rect::FPoint;
+        let p: FPoint = Vec2(1.5, -2.).into();
+        assert_eq!(p, FPoint::new(1.5, -2.));
+        assert_eq!(Vec2::from(p), Vec2(1.5, -2.));
+    }
+}
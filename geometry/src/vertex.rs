@@ -0,0 +1,108 @@
+use crate::{ALL_DIR, HexDir, HexEdge, IHex};
+
+/// Corner shared by three hexes.
+///
+/// Every vertex is stored once, by the hex it is the bottom-right/right or the
+/// right/top-right corner of, in terms of the neighbors it touches.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct HexVertex {
+    hex: IHex,
+    dir: HexDir,
+}
+
+impl HexVertex {
+    /// corner of `hex` between its neighbors in directions `dir` and `dir.rotate(1)`
+    pub fn new(hex: IHex, dir: HexDir) -> Self {
+        use HexDir::*;
+        // the same corner seen from the hex owning it
+        let (hex, dir) = match dir {
+            BotRight | Right => (hex, dir),
+            TopRight => (hex + TopLeft, BotRight),
+            TopLeft => (hex + Left, Right),
+            Left => (hex + Left, BotRight),
+            BotLeft => (hex + BotLeft, Right),
+        };
+        HexVertex { hex, dir }
+    }
+
+    /// hex owning the vertex
+    pub fn hex(&self) -> IHex {
+        self.hex
+    }
+
+    /// first of the two directions from the owning hex to the other hexes
+    pub fn dir(&self) -> HexDir {
+        self.dir
+    }
+
+    /// the owning hex followed by the other two
+    pub fn hexes(&self) -> [IHex; 3] {
+        [self.hex, self.hex + self.dir, self.hex + self.dir.rotate(1)]
+    }
+
+    /// edges between each pair of the hexes
+    pub fn edges(&self) -> [HexEdge; 3] {
+        [
+            HexEdge::new(self.hex, self.dir),
+            HexEdge::new(self.hex, self.dir.rotate(1)),
+            HexEdge::new(self.hex + self.dir, self.dir.rotate(2)),
+        ]
+    }
+}
+
+impl IHex {
+    /// corner between the neighbors in directions `dir` and `dir.rotate(1)`
+    pub fn vertex(self, dir: HexDir) -> HexVertex {
+        HexVertex::new(self, dir)
+    }
+
+    /// corners in the order of `ALL_DIR`, see [`IHex::vertex`]
+    pub fn vertices(self) -> [HexVertex; 6] {
+        ALL_DIR.map(|d| HexVertex::new(self, d))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn canonical() {
+        for hex in IHex(-3, 1).spiral(3) {
+            for d in ALL_DIR {
+                let v = hex.vertex(d);
+                let (a, b) = (hex + d, hex + d.rotate(1));
+                // seen from the other two hexes
+                assert_eq!(v, a.vertex(d.rotate(2)));
+                assert_eq!(v, b.vertex(d.rotate(-2)));
+                let hexes = v.hexes();
+                assert!(hexes.contains(&hex) && hexes.contains(&a) && hexes.contains(&b));
+                assert!(matches!(v.dir(), HexDir::BotRight | HexDir::Right));
+            }
+        }
+    }
+
+    #[test]
+    fn vertices_of_hexagon() {
+        for radius in 0..4 {
+            let hexagon = Hexagon::new(IHEX_0, radius);
+            let vertices: HashSet<HexVertex> =
+                hexagon.hex_iter().flat_map(|h| h.vertices()).collect();
+            assert_eq!(vertices.len(), 6 * (radius as usize + 1).pow(2));
+        }
+    }
+
+    #[test]
+    fn vertex_edges() {
+        for v in IHex(1, 1).vertices() {
+            let edges = v.edges();
+            for e in edges {
+                let [a, b] = e.hexes();
+                assert!(v.hexes().contains(&a) && v.hexes().contains(&b));
+                assert!(e.vertices().contains(&v));
+            }
+            assert_eq!(edges.iter().collect::<HashSet<_>>().len(), 3);
+        }
+    }
+}
@@ -0,0 +1,278 @@
+use crate::geometry::*;
+
+/// Owns the origin and the hex size of a `Layout` and moves them around
+/// to pan and zoom the view of the map inside the window.
+#[derive(Debug, Clone, Copy)]
+pub struct Camera {
+    layout: Layout,
+    viewport: Vec2,
+    /// origin the camera glides to
+    target: Vec2,
+    /// map extent in coordinates of a layout with unit size and zero origin
+    bounds: Option<(Vec2, Vec2)>,
+    min_size: f32,
+    max_size: f32,
+    /// how fast the origin catches up with the target, 1/s
+    smoothing: f32,
+}
+
+impl Camera {
+    pub fn new(layout: Layout, viewport: Vec2) -> Self {
+        Camera {
+            layout,
+            viewport,
+            target: layout.get_origin(),
+            bounds: None,
+            min_size: 4.,
+            max_size: 128.,
+            smoothing: 12.,
+        }
+    }
+
+    pub fn layout(&self) -> &Layout {
+        &self.layout
+    }
+
+    pub fn viewport(&self) -> Vec2 {
+        self.viewport
+    }
+
+    pub fn set_viewport(&mut self, viewport: Vec2) {
+        self.viewport = viewport;
+        self.clamp();
+    }
+
+    pub fn set_orientation(&mut self, orientation: Orientation) {
+        self.layout.set_orientation(orientation);
+        self.bounds = None;
+    }
+
+    /// limits of the hex size in pixels, both axes are limited by the larger one
+    pub fn set_zoom_limits(&mut self, min_size: f32, max_size: f32) {
+        self.min_size = min_size;
+        self.max_size = max_size;
+    }
+
+    /// keep the view inside of the shape
+    pub fn set_bounds(&mut self, shape: &impl Shape) {
+        let unit = Layout::new(self.layout.get_orientation(), Vec2(1., 1.), Vec2(0., 0.));
+        let (mut min, mut max) = (Vec2(f32::MAX, f32::MAX), Vec2(f32::MIN, f32::MIN));
+        for p in unit.hex_centers(shape.hex_iter()) {
+            min = Vec2(min.x().min(p.x()), min.y().min(p.y()));
+            max = Vec2(max.x().max(p.x()), max.y().max(p.y()));
+        }
+        // include hex corners around the centers
+        let corner = Vec2(1., 1.);
+        self.bounds = (min.x() <= max.x()).then_some((min - corner, max + corner));
+        self.clamp();
+    }
+
+    pub fn clear_bounds(&mut self) {
+        self.bounds = None;
+    }
+
+    /// move the view immediately, e.g. following the mouse drag
+    pub fn pan(&mut self, delta: Vec2) {
+        self.layout.set_origin(self.layout.get_origin() + delta);
+        self.target += delta;
+        self.clamp();
+    }
+
+    /// move the view smoothly, the camera catches up on `update`
+    pub fn glide(&mut self, delta: Vec2) {
+        self.target += delta;
+        self.clamp();
+    }
+
+    /// scale hexes by `factor` keeping the point under the cursor in place
+    pub fn zoom_at(&mut self, cursor: Vec2, factor: f32) {
+        let size = self.layout.get_size();
+        let largest = size.x().max(size.y());
+        let factor = factor.clamp(self.min_size / largest, self.max_size / largest);
+        let new_size = size * factor;
+        let scale = |p: Vec2| {
+            let off = cursor - p;
+            cursor - Vec2(off.x() * factor, off.y() * factor)
+        };
+        self.layout.set_size(new_size);
+        self.layout.set_origin(scale(self.layout.get_origin()));
+        self.target = scale(self.target);
+        self.clamp();
+    }
+
+    /// set the hex size ignoring the zoom limits, the center of the viewport stays in place
+    pub fn set_size(&mut self, size: Vec2) {
+        let old = self.layout.get_size();
+        let center = self.viewport * 0.5;
+        let scale = |p: Vec2| {
+            let off = center - p;
+            center - Vec2(off.x() * size.x() / old.x(), off.y() * size.y() / old.y())
+        };
+        self.layout.set_size(size);
+        self.layout.set_origin(scale(self.layout.get_origin()));
+        self.target = scale(self.target);
+        self.clamp();
+    }
+
+    /// advance smooth panning by `dt` seconds
+    pub fn update(&mut self, dt: f32) {
+        let origin = self.layout.get_origin();
+        let t = 1. - (-self.smoothing * dt).exp();
+        self.layout.set_origin(origin + (self.target - origin) * t);
+    }
+
+    /// rectangle of hexes covering the whole viewport
+    pub fn visible_rect(&self) -> Rect {
+        let (p0, p1) = (
+            self.window_to_unit(Vec2(0., 0.)),
+            self.window_to_unit(self.viewport),
+        );
+        let t = self.layout.layout_type();
+        // distance between rows and columns of a unit size layout
+        let (col_step, row_step) = match t {
+            LayoutType::Pointy => (SQRT_3, 1.5),
+            LayoutType::Flat => (1.5, SQRT_3),
+        };
+        let w = ((p1.x() - p0.x()) / col_step).ceil() as i32 + 3;
+        let h = ((p1.y() - p0.y()) / row_step).ceil() as i32 + 3;
+        let h0 = match t {
+            LayoutType::Pointy => {
+                let r = (p0.y() / row_step).floor() as i32 - 1;
+                let q = (p0.x() / col_step - r as f32 / 2.).floor() as i32 - 1;
+                IHex(q, r)
+            }
+            LayoutType::Flat => {
+                let q = (p0.x() / col_step).floor() as i32 - 1;
+                let r = (p0.y() / row_step - q as f32 / 2.).floor() as i32 - 1;
+                IHex(q, r)
+            }
+        };
+        Rect::with_type(h0, w, h, t)
+    }
+
+    /// hexes of the shape intersecting the viewport
+    pub fn visible_hexes<'a>(&self, shape: &'a impl Shape) -> impl Iterator<Item = IHex> + 'a {
+        let rect = self.visible_rect();
+        (0..rect.area())
+            .filter_map(move |i| rect.hex_by_ind(i))
+            .filter(move |h| shape.contains(*h))
+    }
+
+    /// window point in coordinates of a layout with unit size and zero origin
+    fn window_to_unit(&self, p: Vec2) -> Vec2 {
+        let (origin, size) = (self.layout.get_origin(), self.layout.get_size());
+        Vec2(
+            (p.x() - origin.x()) / size.x(),
+            (p.y() - origin.y()) / size.y(),
+        )
+    }
+
+    fn clamp(&mut self) {
+        let Some((min, max)) = self.bounds else {
+            return;
+        };
+        let size = self.layout.get_size();
+        let clamp_axis = |o: f32, min: f32, max: f32, size: f32, view: f32| {
+            let (lo, hi) = (view - max * size, -min * size);
+            if lo > hi {
+                // map is smaller than the view, keep it in the middle
+                (lo + hi) / 2.
+            } else {
+                o.clamp(lo, hi)
+            }
+        };
+        let clamp_point = |o: Vec2| {
+            Vec2(
+                clamp_axis(o.x(), min.x(), max.x(), size.x(), self.viewport.x()),
+                clamp_axis(o.y(), min.y(), max.y(), size.y(), self.viewport.y()),
+            )
+        };
+        self.target = clamp_point(self.target);
+        self.layout
+            .set_origin(clamp_point(self.layout.get_origin()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::camera::Camera;
+    use crate::geometry::*;
+
+    fn camera(orientation: Orientation) -> Camera {
+        let layout = Layout::new(orientation, Vec2(16., 12.), Vec2(-37., 21.));
+        Camera::new(layout, Vec2(800., 600.))
+    }
+
+    #[test]
+    fn visible_rect_covers_viewport() {
+        for orientation in [ORIENTATION_POINTY, ORIENTATION_FLAT] {
+            let cam = camera(orientation);
+            let rect = cam.visible_rect();
+            let layout = cam.layout();
+            for x in (0..=800).step_by(10) {
+                for y in (0..=600).step_by(10) {
+                    let hex = layout.point_to_hex(Vec2(x as f32, y as f32));
+                    assert!(rect.contains(hex), "{orientation:?} {x} {y}");
+                }
+            }
+            // not much larger than the window
+            let cols = 800. / (16. * 1.5);
+            let rows = 600. / (12. * 1.5);
+            assert!((rect.area() as f32) < (cols + 4.) * (rows + 4.) * 1.8);
+        }
+    }
+
+    #[test]
+    fn zoom_keeps_cursor() {
+        let mut cam = camera(ORIENTATION_POINTY);
+        let cursor = Vec2(300., 200.);
+        let before = cam.layout().point_to_fhex(cursor);
+        cam.zoom_at(cursor, 2.);
+        let after = cam.layout().point_to_fhex(cursor);
+        assert!((before.q() - after.q()).abs() < 1e-3);
+        assert!((before.r() - after.r()).abs() < 1e-3);
+        assert_eq!(cam.layout().get_size(), Vec2(32., 24.));
+        cam.zoom_at(cursor, 100.);
+        assert_eq!(cam.layout().get_size().x(), 128.);
+    }
+
+    #[test]
+    fn set_size_keeps_center() {
+        let mut cam = camera(ORIENTATION_FLAT);
+        let center = Vec2(400., 300.);
+        let before = cam.layout().point_to_fhex(center);
+        cam.set_size(Vec2(300., 2.5));
+        assert_eq!(cam.layout().get_size(), Vec2(300., 2.5));
+        let after = cam.layout().point_to_fhex(center);
+        assert!((before.q() - after.q()).abs() < 1e-3);
+        assert!((before.r() - after.r()).abs() < 1e-3);
+    }
+
+    #[test]
+    fn glide_is_smooth() {
+        let mut cam = camera(ORIENTATION_FLAT);
+        let start = cam.layout().get_origin();
+        cam.glide(Vec2(100., 0.));
+        cam.update(1. / 120.);
+        let x = cam.layout().get_origin().x() - start.x();
+        assert!(x > 0. && x < 100.);
+        for _ in 0..240 {
+            cam.update(1. / 120.);
+        }
+        assert!((cam.layout().get_origin().x() - start.x() - 100.).abs() < 0.01);
+    }
+
+    #[test]
+    fn pan_is_clamped() {
+        let mut cam = camera(ORIENTATION_POINTY);
+        let map = Rect::new(IHEX_0, 100, 100);
+        cam.set_bounds(&map);
+        cam.pan(Vec2(10000., 10000.));
+        assert!(cam.layout().point_to_hex(Vec2(0., 0.)).r() >= -1);
+        assert!(cam.visible_hexes(&map).count() > 0);
+        cam.pan(Vec2(-100000., -100000.));
+        let corner = cam.layout().point_to_hex(Vec2(800., 600.));
+        assert!(corner.r() <= 100);
+        assert!(cam.visible_hexes(&map).count() > 0);
+    }
+}
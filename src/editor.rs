@@ -0,0 +1,439 @@
+use crate::geometry::*;
+use crate::map::{HexMap, Layer, MapError, MapFile};
+use sdl2::pixels::Color;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Terrain {
+    Grass,
+    Forest,
+    Hills,
+    Water,
+}
+
+pub const ALL_TERRAIN: [Terrain; 4] = [
+    Terrain::Grass,
+    Terrain::Forest,
+    Terrain::Hills,
+    Terrain::Water,
+];
+
+impl Terrain {
+    pub fn color(self) -> Color {
+        match self {
+            Terrain::Grass => Color::RGB(120, 170, 90),
+            Terrain::Forest => Color::RGB(90, 130, 70),
+            Terrain::Hills => Color::RGB(160, 150, 120),
+            Terrain::Water => Color::RGB(90, 140, 190),
+        }
+    }
+}
+
+/// Terrain of every tile, `None` for erased tiles.
+pub type TerrainMap = HexMap<MapShape, Option<Terrain>>;
+
+/// name of the map file layer with terrain indices into `ALL_TERRAIN`
+pub const TERRAIN_LAYER: &str = "terrain";
+
+/// Area a single stroke paints.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Brush {
+    /// hexes under the cursor
+    Point,
+    /// straight line from the start of the stroke to its end
+    Line,
+    /// rectangle with the start and the end of the stroke in its corners
+    Rect(LayoutType),
+    /// hexagon of the radius around the cursor
+    Radius(i32),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Tool {
+    Paint(Terrain),
+    Erase,
+    /// replace the connected area of the same terrain
+    Fill(Terrain),
+}
+
+/// value of a tile before and after an edit
+#[derive(Clone, Copy, Debug)]
+struct Change {
+    hex: IHex,
+    before: Option<Terrain>,
+    after: Option<Terrain>,
+}
+
+#[derive(Clone, Debug)]
+struct Stroke {
+    start: IHex,
+    last: IHex,
+    /// tiles changed so far with their values before the stroke
+    before: HashMap<IHex, Option<Terrain>>,
+}
+
+/// Paints terrain with brushes and keeps the history of strokes for undo and redo.
+#[derive(Clone, Debug)]
+pub struct Editor {
+    map: TerrainMap,
+    pub brush: Brush,
+    pub tool: Tool,
+    stroke: Option<Stroke>,
+    undo: Vec<Vec<Change>>,
+    redo: Vec<Vec<Change>>,
+}
+
+impl Editor {
+    pub fn new(map: TerrainMap) -> Self {
+        Editor {
+            map,
+            brush: Brush::Point,
+            tool: Tool::Paint(Terrain::Grass),
+            stroke: None,
+            undo: Vec::new(),
+            redo: Vec::new(),
+        }
+    }
+
+    pub fn map(&self) -> &TerrainMap {
+        &self.map
+    }
+
+    pub fn terrain(&self, hex: IHex) -> Option<Terrain> {
+        self.map.get(hex).copied().flatten()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// start a stroke at the hex, fills are applied at once
+    pub fn begin_stroke(&mut self, hex: IHex) {
+        self.stroke = Some(Stroke {
+            start: hex,
+            last: hex,
+            before: HashMap::new(),
+        });
+        match (self.tool, self.brush) {
+            (Tool::Fill(terrain), _) => {
+                for h in self.flood(hex) {
+                    self.paint(h, Some(terrain));
+                }
+                self.end_stroke();
+            }
+            (_, Brush::Point | Brush::Radius(_)) => self.stamp(hex),
+            _ => {}
+        }
+    }
+
+    /// continue the stroke to the hex under the cursor
+    pub fn stroke_to(&mut self, hex: IHex) {
+        let Some(stroke) = &mut self.stroke else {
+            return;
+        };
+        let last = std::mem::replace(&mut stroke.last, hex);
+        if let Brush::Point | Brush::Radius(_) = self.brush {
+            // no gaps when the cursor skips hexes between two frames
+            for h in last.line(hex).skip(1) {
+                self.stamp(h);
+            }
+        }
+    }
+
+    /// finish the stroke, line and rectangle brushes paint now
+    pub fn end_stroke(&mut self) {
+        if let Some(stroke) = &self.stroke
+            && let Brush::Line | Brush::Rect(_) = self.brush
+            && !matches!(self.tool, Tool::Fill(_))
+        {
+            for h in self.brush_hexes(stroke.start, stroke.last) {
+                self.paint(h, self.tool_value());
+            }
+        }
+        let Some(stroke) = self.stroke.take() else {
+            return;
+        };
+        let edit: Vec<Change> = stroke
+            .before
+            .into_iter()
+            .map(|(hex, before)| Change {
+                hex,
+                before,
+                after: self.terrain(hex),
+            })
+            .filter(|c| c.before != c.after)
+            .collect();
+        if !edit.is_empty() {
+            self.undo.push(edit);
+            self.redo.clear();
+        }
+    }
+
+    /// hexes the brush would paint with the cursor over the hex
+    pub fn preview(&self, cursor: IHex) -> Vec<IHex> {
+        let start = match (&self.stroke, self.brush) {
+            (Some(stroke), Brush::Line | Brush::Rect(_)) => stroke.start,
+            _ => cursor,
+        };
+        match self.tool {
+            Tool::Fill(_) => vec![cursor],
+            _ => self.brush_hexes(start, cursor),
+        }
+    }
+
+    pub fn undo(&mut self) -> bool {
+        let Some(edit) = self.undo.pop() else {
+            return false;
+        };
+        for c in &edit {
+            self.map.set(c.hex, c.before);
+        }
+        self.redo.push(edit);
+        true
+    }
+
+    pub fn redo(&mut self) -> bool {
+        let Some(edit) = self.redo.pop() else {
+            return false;
+        };
+        for c in &edit {
+            self.map.set(c.hex, c.after);
+        }
+        self.undo.push(edit);
+        true
+    }
+
+    /// map file with the terrain layer, erased tiles are left out;
+    /// a rectangle keeps the layout its rows are shifted for whatever the view shows
+    pub fn to_file(&self, layout: &Layout) -> MapFile {
+        let layout_type = match self.map.shape() {
+            MapShape::Rect(r) => r.layout_type(),
+            MapShape::Hexagon(_) => layout.layout_type(),
+        };
+        let mut file = MapFile::new(*self.map.shape(), layout_type, layout.get_size());
+        let tiles = self
+            .map
+            .iter()
+            .filter_map(|(hex, t)| {
+                let i = ALL_TERRAIN.iter().position(|a| Some(*a) == *t)?;
+                Some((hex, i as u32))
+            })
+            .collect();
+        file.layers.push(Layer {
+            name: TERRAIN_LAYER.to_string(),
+            tiles,
+        });
+        file
+    }
+
+    /// replace the map with the terrain of the file, the history is dropped
+    pub fn load_file(&mut self, file: &MapFile) -> Result<(), MapError> {
+        let mut map = TerrainMap::new(file.shape, None);
+        if let Some(layer) = file.layer(TERRAIN_LAYER) {
+            for &(hex, value) in &layer.tiles {
+                let terrain = ALL_TERRAIN.get(value as usize).ok_or_else(|| {
+                    MapError::Invalid(format!("unknown terrain {value} at {hex:?}"))
+                })?;
+                map.set(hex, Some(*terrain));
+            }
+        }
+        self.map = map;
+        self.stroke = None;
+        self.undo.clear();
+        self.redo.clear();
+        Ok(())
+    }
+
+    fn tool_value(&self) -> Option<Terrain> {
+        match self.tool {
+            Tool::Paint(t) | Tool::Fill(t) => Some(t),
+            Tool::Erase => None,
+        }
+    }
+
+    fn brush_hexes(&self, start: IHex, end: IHex) -> Vec<IHex> {
+        match self.brush {
+            Brush::Point => vec![end],
+            Brush::Line => Line::new(start, end).hex_iter().collect(),
+            Brush::Rect(t) => Rect::from_corners(start, end, t).hex_iter().collect(),
+            Brush::Radius(r) => end.spiral(r).collect(),
+        }
+    }
+
+    fn stamp(&mut self, hex: IHex) {
+        let value = self.tool_value();
+        for h in self.brush_hexes(hex, hex) {
+            self.paint(h, value);
+        }
+    }
+
+    /// set the tile remembering its value before the stroke
+    fn paint(&mut self, hex: IHex, value: Option<Terrain>) {
+        let Some(tile) = self.map.get_mut(hex) else {
+            return;
+        };
+        let before = std::mem::replace(tile, value);
+        if let Some(stroke) = &mut self.stroke {
+            stroke.before.entry(hex).or_insert(before);
+        }
+    }
+
+    /// connected tiles of the map with the same terrain as the hex
+    fn flood(&self, hex: IHex) -> Vec<IHex> {
+        let Some(&target) = self.map.get(hex) else {
+            return Vec::new();
+        };
+        let mut visited = HashSet::from([hex]);
+        let mut frontier = VecDeque::from([hex]);
+        let mut area = Vec::new();
+        while let Some(current) = frontier.pop_front() {
+            area.push(current);
+            for next in current.neighbors() {
+                if self.map.get(next) == Some(&target) && visited.insert(next) {
+                    frontier.push_back(next);
+                }
+            }
+        }
+        area
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::editor::{Brush, Editor, Terrain, TerrainMap, Tool};
+    use crate::geometry::*;
+    use crate::map::MapError;
+
+    fn editor() -> Editor {
+        Editor::new(TerrainMap::new(
+            MapShape::Rect(Rect::new(IHEX_0, 10, 10)),
+            Some(Terrain::Grass),
+        ))
+    }
+
+    fn count(editor: &Editor, terrain: Option<Terrain>) -> usize {
+        editor
+            .map()
+            .values()
+            .iter()
+            .filter(|t| **t == terrain)
+            .count()
+    }
+
+    #[test]
+    fn point_and_radius_strokes() {
+        let mut ed = editor();
+        ed.tool = Tool::Paint(Terrain::Water);
+        ed.begin_stroke(IHex(1, 1));
+        // the cursor jumped over three hexes
+        ed.stroke_to(IHex(5, 1));
+        ed.end_stroke();
+        assert_eq!(count(&ed, Some(Terrain::Water)), 5);
+        ed.brush = Brush::Radius(1);
+        ed.tool = Tool::Erase;
+        ed.begin_stroke(IHex(3, 5));
+        ed.end_stroke();
+        assert_eq!(count(&ed, None), 7);
+        // painting outside of the map is ignored
+        ed.begin_stroke(IHex(-20, 0));
+        ed.end_stroke();
+        assert_eq!(ed.map().len(), 100);
+    }
+
+    #[test]
+    fn line_and_rect_on_release() {
+        let mut ed = editor();
+        ed.tool = Tool::Paint(Terrain::Hills);
+        ed.brush = Brush::Line;
+        ed.begin_stroke(IHex(0, 0));
+        ed.stroke_to(IHex(2, 2));
+        ed.stroke_to(IHex(4, 0));
+        assert_eq!(count(&ed, Some(Terrain::Hills)), 0);
+        assert_eq!(ed.preview(IHex(4, 0)).len(), 5);
+        ed.end_stroke();
+        assert_eq!(count(&ed, Some(Terrain::Hills)), 5);
+        ed.brush = Brush::Rect(LayoutType::Pointy);
+        ed.tool = Tool::Paint(Terrain::Forest);
+        ed.begin_stroke(IHex(2, 4));
+        ed.stroke_to(IHex(4, 6));
+        ed.end_stroke();
+        // four columns in three rows
+        assert_eq!(count(&ed, Some(Terrain::Forest)), 12);
+    }
+
+    #[test]
+    fn flood_fill() {
+        let mut ed = editor();
+        ed.tool = Tool::Paint(Terrain::Water);
+        ed.brush = Brush::Line;
+        // wall across the sixth row splits the map in two
+        ed.begin_stroke(IHex(-2, 5));
+        ed.stroke_to(IHex(7, 5));
+        ed.end_stroke();
+        ed.tool = Tool::Fill(Terrain::Forest);
+        ed.begin_stroke(IHex(0, 0));
+        assert_eq!(count(&ed, Some(Terrain::Forest)), 50);
+        assert_eq!(count(&ed, Some(Terrain::Grass)), 40);
+        // filling with the same terrain changes nothing
+        let undo_len = ed.undo.len();
+        ed.begin_stroke(IHex(0, 0));
+        assert_eq!(ed.undo.len(), undo_len);
+    }
+
+    #[test]
+    fn undo_redo() {
+        let mut ed = editor();
+        ed.tool = Tool::Paint(Terrain::Water);
+        ed.brush = Brush::Radius(2);
+        ed.begin_stroke(IHex(4, 4));
+        ed.stroke_to(IHex(5, 4));
+        ed.end_stroke();
+        let painted = count(&ed, Some(Terrain::Water));
+        ed.tool = Tool::Erase;
+        ed.brush = Brush::Point;
+        ed.begin_stroke(IHex(4, 4));
+        ed.end_stroke();
+        assert!(ed.undo());
+        assert_eq!(count(&ed, Some(Terrain::Water)), painted);
+        assert!(ed.undo());
+        assert_eq!(count(&ed, Some(Terrain::Grass)), 100);
+        assert!(!ed.undo());
+        assert!(ed.redo());
+        assert_eq!(count(&ed, Some(Terrain::Water)), painted);
+        // a new stroke drops the redo history
+        ed.begin_stroke(IHex(0, 0));
+        ed.end_stroke();
+        assert!(!ed.can_redo());
+    }
+
+    #[test]
+    fn file_round_trip() {
+        let mut ed = editor();
+        ed.tool = Tool::Erase;
+        ed.begin_stroke(IHex(3, 3));
+        ed.end_stroke();
+        ed.tool = Tool::Paint(Terrain::Hills);
+        ed.begin_stroke(IHex(4, 3));
+        ed.end_stroke();
+        let layout = Layout::new(ORIENTATION_FLAT, Vec2(20., 18.), Vec2(0., 0.));
+        let file = ed.to_file(&layout);
+        assert_eq!(file.layout, LayoutType::Pointy);
+        file.validate().unwrap();
+        assert_eq!(file.layer("terrain").unwrap().tiles.len(), 99);
+
+        let mut loaded = editor();
+        loaded.begin_stroke(IHex(0, 0));
+        loaded.end_stroke();
+        loaded.load_file(&file).unwrap();
+        assert_eq!(loaded.map().values(), ed.map().values());
+        assert!(!loaded.can_undo());
+
+        let mut bad = file.clone();
+        bad.layers[0].tiles[0].1 = 9;
+        assert!(matches!(loaded.load_file(&bad), Err(MapError::Invalid(_))));
+    }
+}
@@ -1,13 +0,0 @@
-mod hex;
-mod shape;
-mod point;
-mod layout;
-mod rect;
-mod line;
-
-pub use hex::*;
-pub use shape::*;
-pub use point::*;
-pub use layout::*;
-pub use rect::*;
-pub use line::*;
\ No newline at end of file
@@ -0,0 +1,473 @@
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::{MouseButton, MouseWheelDirection};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+/// Physical input an action can be bound to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Input {
+    Key(Keycode),
+    Mouse(MouseButton),
+    WheelUp,
+    WheelDown,
+}
+
+/// keys with names usable in the config, letters and digits are added in `key_by_name`
+const KEY_NAMES: [(&str, Keycode); 36] = [
+    ("Escape", Keycode::Escape),
+    ("Tab", Keycode::Tab),
+    ("Space", Keycode::Space),
+    ("Return", Keycode::Return),
+    ("Backspace", Keycode::Backspace),
+    ("Delete", Keycode::Delete),
+    ("Insert", Keycode::Insert),
+    ("Home", Keycode::Home),
+    ("End", Keycode::End),
+    ("PageUp", Keycode::PageUp),
+    ("PageDown", Keycode::PageDown),
+    ("Left", Keycode::Left),
+    ("Right", Keycode::Right),
+    ("Up", Keycode::Up),
+    ("Down", Keycode::Down),
+    ("LShift", Keycode::LShift),
+    ("RShift", Keycode::RShift),
+    ("LCtrl", Keycode::LCtrl),
+    ("RCtrl", Keycode::RCtrl),
+    ("LAlt", Keycode::LAlt),
+    ("RAlt", Keycode::RAlt),
+    ("Minus", Keycode::Minus),
+    ("Equals", Keycode::Equals),
+    ("F1", Keycode::F1),
+    ("F2", Keycode::F2),
+    ("F3", Keycode::F3),
+    ("F4", Keycode::F4),
+    ("F5", Keycode::F5),
+    ("F6", Keycode::F6),
+    ("F7", Keycode::F7),
+    ("F8", Keycode::F8),
+    ("F9", Keycode::F9),
+    ("F10", Keycode::F10),
+    ("F11", Keycode::F11),
+    ("F12", Keycode::F12),
+    ("Backquote", Keycode::Backquote),
+];
+
+const MOUSE_NAMES: [(&str, MouseButton); 6] = [
+    ("Mouse:Left", MouseButton::Left),
+    ("Mouse:Middle", MouseButton::Middle),
+    ("Mouse:Right", MouseButton::Right),
+    ("Mouse:X1", MouseButton::X1),
+    ("Mouse:X2", MouseButton::X2),
+    ("Mouse:Unknown", MouseButton::Unknown),
+];
+
+fn key_by_name(name: &str) -> Option<Keycode> {
+    if let Some((_, key)) = KEY_NAMES.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)) {
+        return Some(*key);
+    }
+    // keys without a name are written as their SDL key code, e.g. `Key:1073741912`
+    if let Some(code) = name.get(..4).filter(|p| p.eq_ignore_ascii_case("Key:")) {
+        return Keycode::from_i32(name[code.len()..].parse().ok()?);
+    }
+    // letters and digits are their own ASCII codes
+    match name.as_bytes() {
+        [c] if c.is_ascii_alphanumeric() => Keycode::from_i32(c.to_ascii_lowercase() as i32),
+        _ => None,
+    }
+}
+
+impl Input {
+    /// parse the config name, e.g. `W`, `Escape`, `Mouse:Right` or `Wheel:Up`
+    pub fn from_name(name: &str) -> Option<Input> {
+        if name.eq_ignore_ascii_case("Wheel:Up") {
+            return Some(Input::WheelUp);
+        }
+        if name.eq_ignore_ascii_case("Wheel:Down") {
+            return Some(Input::WheelDown);
+        }
+        if let Some((_, b)) = MOUSE_NAMES
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        {
+            return Some(Input::Mouse(*b));
+        }
+        key_by_name(name).map(Input::Key)
+    }
+
+    /// config name of the input, keys without a name get their key code
+    pub fn name(&self) -> String {
+        match self {
+            Input::WheelUp => "Wheel:Up".to_string(),
+            Input::WheelDown => "Wheel:Down".to_string(),
+            Input::Mouse(b) => MOUSE_NAMES
+                .iter()
+                .find(|(_, m)| m == b)
+                .map(|(n, _)| n.to_string())
+                .expect("every mouse button has a name"),
+            Input::Key(k) => {
+                if let Some((n, _)) = KEY_NAMES.iter().find(|(_, key)| key == k) {
+                    return n.to_string();
+                }
+                u8::try_from(k.into_i32())
+                    .ok()
+                    .filter(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+                    .map(|c| (c.to_ascii_uppercase() as char).to_string())
+                    .unwrap_or_else(|| format!("Key:{}", k.into_i32()))
+            }
+        }
+    }
+}
+
+/// Maps keys, mouse buttons and the wheel to named actions and tracks their state per frame.
+///
+/// Feed every event to `handle_event` and call `begin_frame` before polling the events
+/// of the next frame. An action is held while any of its inputs is held.
+#[derive(Clone, Debug, Default)]
+pub struct InputMap {
+    bindings: HashMap<Input, Vec<String>>,
+    held: HashSet<Input>,
+    /// presses since the start of the frame, wheel steps count as presses
+    pressed: HashMap<Input, u32>,
+    released: HashSet<Input>,
+}
+
+impl InputMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// add the binding, an input may trigger several actions
+    pub fn bind(&mut self, input: Input, action: &str) {
+        let actions = self.bindings.entry(input).or_default();
+        if !actions.iter().any(|a| a == action) {
+            actions.push(action.to_string());
+        }
+    }
+
+    /// remove all bindings of the action
+    pub fn unbind_action(&mut self, action: &str) {
+        for actions in self.bindings.values_mut() {
+            actions.retain(|a| a != action);
+        }
+        self.bindings.retain(|_, actions| !actions.is_empty());
+    }
+
+    /// inputs bound to the action
+    pub fn bindings<'a>(&'a self, action: &'a str) -> impl Iterator<Item = Input> + 'a {
+        self.bindings
+            .iter()
+            .filter(move |(_, actions)| actions.iter().any(|a| a == action))
+            .map(|(input, _)| *input)
+    }
+
+    /// rebind actions listed in the config text, other actions keep their bindings.
+    ///
+    /// Every line is `action = input, input, ...`, `#` starts a comment.
+    /// An action with nothing after `=` is left unbound.
+    pub fn apply_config(&mut self, text: &str) -> Result<(), String> {
+        let mut parsed = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (action, inputs) = line
+                .split_once('=')
+                .ok_or_else(|| format!("line {}: expected `action = input, ...`", i + 1))?;
+            let action = action.trim();
+            if action.is_empty() {
+                return Err(format!("line {}: missing action name", i + 1));
+            }
+            let inputs = inputs
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(|name| {
+                    Input::from_name(name)
+                        .ok_or_else(|| format!("line {}: unknown input `{name}`", i + 1))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            parsed.push((action, inputs));
+        }
+        // nothing is changed when the config has errors
+        for (action, inputs) in parsed {
+            self.unbind_action(action);
+            for input in inputs {
+                self.bind(input, action);
+            }
+        }
+        Ok(())
+    }
+
+    /// rebind actions from the config file, see `apply_config`
+    pub fn load_config(&mut self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        self.apply_config(&text)
+            .map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    /// all bindings in the config format, actions sorted by name
+    pub fn to_config(&self) -> String {
+        let mut actions: Vec<&str> = self
+            .bindings
+            .values()
+            .flatten()
+            .map(String::as_str)
+            .collect();
+        actions.sort();
+        actions.dedup();
+        let mut out = String::new();
+        for action in actions {
+            let mut names: Vec<String> = self.bindings(action).map(|i| i.name()).collect();
+            names.sort();
+            out += &format!("{action} = {}\n", names.join(", "));
+        }
+        out
+    }
+
+    /// forget presses and releases of the previous frame
+    pub fn begin_frame(&mut self) {
+        self.pressed.clear();
+        self.released.clear();
+        // wheel steps are never held
+        self.held.remove(&Input::WheelUp);
+        self.held.remove(&Input::WheelDown);
+    }
+
+    /// update the state from the event, returns true if the event is bound to an action
+    pub fn handle_event(&mut self, event: &Event) -> bool {
+        match *event {
+            Event::KeyDown {
+                keycode: Some(key),
+                repeat,
+                ..
+            } => {
+                if repeat {
+                    return self.bindings.contains_key(&Input::Key(key));
+                }
+                self.press(Input::Key(key), 1)
+            }
+            Event::KeyUp {
+                keycode: Some(key), ..
+            } => self.release(Input::Key(key)),
+            Event::MouseButtonDown { mouse_btn, .. } => self.press(Input::Mouse(mouse_btn), 1),
+            Event::MouseButtonUp { mouse_btn, .. } => self.release(Input::Mouse(mouse_btn)),
+            Event::MouseWheel { y, direction, .. } => {
+                let y = match direction {
+                    MouseWheelDirection::Flipped => -y,
+                    _ => y,
+                };
+                match y {
+                    0 => false,
+                    y if y > 0 => self.press(Input::WheelUp, y as u32),
+                    y => self.press(Input::WheelDown, y.unsigned_abs()),
+                }
+            }
+            _ => false,
+        }
+    }
+
+    /// the action is held down
+    pub fn held(&self, action: &str) -> bool {
+        self.bindings(action).any(|i| self.held.contains(&i))
+    }
+
+    /// the action was triggered this frame
+    pub fn pressed(&self, action: &str) -> bool {
+        self.presses(action) > 0
+    }
+
+    /// how many times the action was triggered this frame, e.g. wheel steps
+    pub fn presses(&self, action: &str) -> u32 {
+        self.bindings(action)
+            .filter_map(|i| self.pressed.get(&i))
+            .sum()
+    }
+
+    /// the action was let go this frame and none of its inputs is held anymore
+    pub fn released(&self, action: &str) -> bool {
+        !self.held(action) && self.bindings(action).any(|i| self.released.contains(&i))
+    }
+
+    fn press(&mut self, input: Input, count: u32) -> bool {
+        self.held.insert(input);
+        *self.pressed.entry(input).or_default() += count;
+        self.bindings.contains_key(&input)
+    }
+
+    fn release(&mut self, input: Input) -> bool {
+        if self.held.remove(&input) {
+            self.released.insert(input);
+        }
+        self.bindings.contains_key(&input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input::{Input, InputMap};
+    use sdl2::event::Event;
+    use sdl2::keyboard::{Keycode, Mod};
+    use sdl2::mouse::{MouseButton, MouseWheelDirection};
+
+    fn key(keycode: Keycode, down: bool, repeat: bool) -> Event {
+        if down {
+            Event::KeyDown {
+                timestamp: 0,
+                window_id: 0,
+                keycode: Some(keycode),
+                scancode: None,
+                keymod: Mod::NOMOD,
+                repeat,
+            }
+        } else {
+            Event::KeyUp {
+                timestamp: 0,
+                window_id: 0,
+                keycode: Some(keycode),
+                scancode: None,
+                keymod: Mod::NOMOD,
+                repeat,
+            }
+        }
+    }
+
+    fn mouse(mouse_btn: MouseButton, down: bool) -> Event {
+        if down {
+            Event::MouseButtonDown {
+                timestamp: 0,
+                window_id: 0,
+                which: 0,
+                mouse_btn,
+                clicks: 1,
+                x: 0,
+                y: 0,
+            }
+        } else {
+            Event::MouseButtonUp {
+                timestamp: 0,
+                window_id: 0,
+                which: 0,
+                mouse_btn,
+                clicks: 1,
+                x: 0,
+                y: 0,
+            }
+        }
+    }
+
+    fn wheel(y: i32) -> Event {
+        Event::MouseWheel {
+            timestamp: 0,
+            window_id: 0,
+            which: 0,
+            x: 0,
+            y,
+            direction: MouseWheelDirection::Normal,
+            precise_x: 0.,
+            precise_y: y as f32,
+            mouse_x: 0,
+            mouse_y: 0,
+        }
+    }
+
+    fn input() -> InputMap {
+        let mut map = InputMap::new();
+        map.bind(Input::Key(Keycode::W), "up");
+        map.bind(Input::Key(Keycode::Up), "up");
+        map.bind(Input::Mouse(MouseButton::Right), "drag");
+        map.bind(Input::WheelUp, "zoom_in");
+        map
+    }
+
+    #[test]
+    fn held_and_pressed() {
+        let mut map = input();
+        assert!(map.handle_event(&key(Keycode::W, true, false)));
+        assert!(map.pressed("up") && map.held("up"));
+        map.begin_frame();
+        assert!(map.handle_event(&key(Keycode::W, true, true)));
+        assert!(!map.pressed("up") && map.held("up"));
+        // second key of the same action keeps it held
+        map.handle_event(&key(Keycode::Up, true, false));
+        map.handle_event(&key(Keycode::W, false, false));
+        assert!(map.held("up") && !map.released("up"));
+        map.begin_frame();
+        map.handle_event(&key(Keycode::Up, false, false));
+        assert!(!map.held("up") && map.released("up"));
+        map.begin_frame();
+        assert!(!map.released("up"));
+        assert!(!map.handle_event(&key(Keycode::Q, true, false)));
+        assert!(!map.held("unknown"));
+    }
+
+    #[test]
+    fn mouse_and_wheel() {
+        let mut map = input();
+        map.handle_event(&mouse(MouseButton::Right, true));
+        map.handle_event(&wheel(2));
+        map.handle_event(&wheel(1));
+        assert!(!map.handle_event(&wheel(-1)));
+        assert!(map.held("drag"));
+        assert_eq!(map.presses("zoom_in"), 3);
+        map.begin_frame();
+        assert!(map.held("drag"));
+        assert!(!map.held("zoom_in") && map.presses("zoom_in") == 0);
+        map.handle_event(&mouse(MouseButton::Right, false));
+        assert!(!map.held("drag") && map.released("drag"));
+    }
+
+    #[test]
+    fn config_rebinding() {
+        let mut map = input();
+        let config = "
+            # comments and blank lines are skipped
+            up = I, Mouse:Middle
+            zoom_in =
+            quit = Escape, Wheel:Down  # trailing comment
+        ";
+        map.apply_config(config).unwrap();
+        map.handle_event(&key(Keycode::W, true, false));
+        map.handle_event(&key(Keycode::I, true, false));
+        assert!(map.held("up"));
+        assert_eq!(map.bindings("up").count(), 2);
+        assert_eq!(map.bindings("zoom_in").count(), 0);
+        assert_eq!(map.bindings("drag").count(), 1);
+        let text = map.to_config();
+        assert_eq!(
+            text,
+            "drag = Mouse:Right\nquit = Escape, Wheel:Down\nup = I, Mouse:Middle\n"
+        );
+        let mut copy = InputMap::new();
+        copy.apply_config(&text).unwrap();
+        assert_eq!(copy.to_config(), text);
+    }
+
+    #[test]
+    fn config_unnamed_keys() {
+        let mut map = InputMap::new();
+        map.bind(Input::Key(Keycode::KpEnter), "confirm");
+        map.bind(Input::Mouse(MouseButton::Unknown), "confirm");
+        let text = map.to_config();
+        let code = Keycode::KpEnter.into_i32();
+        assert_eq!(text, format!("confirm = Key:{code}, Mouse:Unknown\n"));
+        let mut copy = InputMap::new();
+        copy.apply_config(&text).unwrap();
+        assert_eq!(copy.to_config(), text);
+        assert_eq!(Input::from_name("key:abc"), None);
+    }
+
+    #[test]
+    fn config_errors() {
+        let mut map = input();
+        let err = map.apply_config("up = W\nleft = Nope").unwrap_err();
+        assert_eq!(err, "line 2: unknown input `Nope`");
+        // a broken config does not change anything
+        assert_eq!(map.bindings("up").count(), 2);
+        assert!(map.apply_config("just words").is_err());
+        assert!(map.apply_config(" = W").is_err());
+    }
+}
@@ -0,0 +1,13 @@
+//! Everything of the editor but its window and main loop.
+//!
+//! The library is built with the SDL2 dependency of the package, hex math without SDL
+//! lives in the `coagula-geometry` crate.
+
+use coagula_geometry as geometry;
+
+pub mod camera;
+pub mod editor;
+pub mod input;
+pub mod map;
+pub mod render;
+pub mod selection;
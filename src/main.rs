@@ -1,12 +1,20 @@
-mod geometry;
-
+use camera::Camera;
+use coagula_geometry as geometry;
+use coagula_sdl::{camera, editor, input, map, render, selection};
+use editor::{ALL_TERRAIN, Brush, Editor, Terrain, TerrainMap, Tool};
 use geometry::*;
+use input::{Input, InputMap};
+use map::MapFile;
+use render::{GridMesh, Renderer, SoftwareRenderer, SpriteAtlas};
 use sdl2::event::Event;
 use sdl2::keyboard::Keycode;
-use sdl2::pixels::Color;
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::{Color, PixelFormatEnum};
 use sdl2::rect::FPoint;
-use sdl2::render::Canvas;
-use sdl2::video::Window;
+use sdl2::render::{BlendMode, Texture, TextureCreator};
+use sdl2::surface::Surface;
+use selection::{DragShape, Selection};
+use std::time::Instant;
 
 pub fn main() {
     // Constants for better readability
@@ -14,9 +22,13 @@ pub fn main() {
     const WINDOW_WIDTH: u32 = 800;
     const WINDOW_HEIGHT: u32 = 600;
     const FRAME_DURATION: std::time::Duration = std::time::Duration::new(0, 1_000_000_000u32 / 120);
+    const PAN_SPEED: f32 = 600.; // pixels per second
+    const ZOOM_STEP: f32 = 1.1;
+    const SCREENSHOT_PATH: &str = "screenshot.png";
+    const INPUT_CONFIG_PATH: &str = "input.cfg";
+    const MAP_PATH: &str = "map.ron";
 
     const SAGE: Color = Color::RGB(197, 195, 146);
-    const HONEYDEW: Color = Color::RGB(235, 245, 223);
     const BLACK: Color = Color::RGB(0, 0, 0);
 
     // Initialize SDL2 and create window and canvas
@@ -28,86 +40,396 @@ pub fn main() {
         .build()
         .unwrap();
     let mut canvas = window.into_canvas().build().unwrap();
+    canvas.set_blend_mode(BlendMode::Blend);
+    let texture_creator = canvas.texture_creator();
+    let (terrain_texture, terrain_atlas) = terrain_atlas(&texture_creator).unwrap();
 
-    // Layout setup
-    let origin = FPoint::new(16., 16.);
-    let mut size = FPoint::new(16., 16.);
-    let mut layout = Layout::new(ORIENTATION_POINTY, origin, size);
+    // Map and camera setup
+    let mut orientation = ORIENTATION_POINTY;
+    let mut map = MapShape::Rect(Rect::with_type(IHEX_0, 64, 64, orientation.layout_type()));
+    let origin = Vec2(16., 16.);
+    let size = Vec2(16., 16.);
+    let viewport = Vec2(WINDOW_WIDTH as f32, WINDOW_HEIGHT as f32);
+    let mut camera = Camera::new(Layout::new(orientation, size, origin), viewport);
+    camera.set_bounds(&map);
+    let mut grid = GridMesh::new(BLACK, 1.);
+    let mut selection = Selection::new();
+    let mut editor = Editor::new(placeholder_terrain(map));
+    let mut editing = false;
 
     // Initial rendering
     canvas.set_draw_color(SAGE);
     canvas.clear();
     canvas.present();
 
-    let mut mouse_pos = FPoint::new(0., 0.);
+    // Actions with default bindings, rebound by the config file if there is one
+    let mut input = default_input();
+    if std::path::Path::new(INPUT_CONFIG_PATH).exists()
+        && let Err(e) = input.load_config(INPUT_CONFIG_PATH)
+    {
+        eprintln!("{e}");
+    }
+
+    let mut mouse_pos = Vec2(0., 0.);
     let mut event_pump = sdl_context.event_pump().unwrap();
+    let mut last_frame = Instant::now();
 
     // Main loop
     'running: loop {
+        input.begin_frame();
         for event in event_pump.poll_iter() {
+            input.handle_event(&event);
             match event {
-                Event::KeyDown {
-                    keycode: Some(code),
-                    ..
-                } => {
-                    size = match code {
-                        Keycode::W => FPoint::new(size.x(), size.y() + 1.),
-                        Keycode::S => FPoint::new(size.x(), size.y() - 1.),
-                        Keycode::A => FPoint::new(size.x() - 1., size.y()),
-                        Keycode::D => FPoint::new(size.x() + 1., size.y()),
-                        Keycode::Escape => break 'running,
-                        _ => size,
-                    };
-                    layout = Layout::new(ORIENTATION_POINTY, size, origin); // Update layout
-                }
                 Event::Quit { .. } => break 'running,
-                Event::MouseMotion { x, y, .. } => {
-                    mouse_pos = FPoint::new(x as f32, y as f32);
+                Event::MouseMotion {
+                    x, y, xrel, yrel, ..
+                } => {
+                    mouse_pos = Vec2(x as f32, y as f32);
+                    let hex = camera.layout().point_to_hex(mouse_pos);
+                    selection.drag_to(hex);
+                    editor.stroke_to(hex);
+                    if input.held("drag_pan") {
+                        camera.pan(Vec2(xrel as f32, yrel as f32));
+                    }
                 }
                 _ => {}
             }
         }
 
+        if input.pressed("quit") {
+            break 'running;
+        }
+        if input.pressed("toggle_orientation") {
+            orientation = match orientation.layout_type() {
+                LayoutType::Flat => ORIENTATION_POINTY,
+                LayoutType::Pointy => ORIENTATION_FLAT,
+            };
+            // only the view changes, the map keeps its shape and terrain
+            camera.set_orientation(orientation);
+            camera.set_bounds(&map);
+        }
+
+        if input.pressed("toggle_editor") {
+            editing = !editing;
+            selection.clear();
+            editor.end_stroke();
+        }
+        let hovered_hex = camera.layout().point_to_hex(mouse_pos);
+        if editing {
+            edit_map(&mut editor, &input, hovered_hex, orientation.layout_type());
+            if input.pressed("save_map")
+                && let Err(e) = editor.to_file(camera.layout()).save_text(MAP_PATH)
+            {
+                eprintln!("{MAP_PATH}: {e}");
+            }
+            if input.pressed("load_map") {
+                match MapFile::load(MAP_PATH).and_then(|file| {
+                    editor.load_file(&file)?;
+                    Ok(file)
+                }) {
+                    Ok(file) => {
+                        map = file.shape;
+                        orientation = file.layout.orientation();
+                        camera.set_orientation(orientation);
+                        camera.set_size(file.hex_size);
+                        camera.set_bounds(&map);
+                        grid.invalidate();
+                    }
+                    Err(e) => eprintln!("{MAP_PATH}: {e}"),
+                }
+            }
+        } else {
+            // Selection with clicks and drags, shift extends a click into a line
+            if input.pressed("select") {
+                let shape = if input.held("select_rect") {
+                    DragShape::Rect(orientation.layout_type())
+                } else {
+                    DragShape::Hexagon
+                };
+                selection.press(hovered_hex, shape);
+            }
+            if input.released("select") {
+                selection.release(input.held("select_line"));
+            }
+            if input.pressed("clear_selection") {
+                selection.clear();
+            }
+        }
+
+        let zoom = input.presses("zoom_in") as i32 - input.presses("zoom_out") as i32;
+        if zoom != 0 {
+            camera.zoom_at(mouse_pos, ZOOM_STEP.powi(zoom));
+        }
+
+        // Smooth panning with held keys
+        let dt = last_frame.elapsed().as_secs_f32();
+        last_frame = Instant::now();
+        let mut pan = Vec2(0., 0.);
+        if input.held("pan_left") {
+            pan += Vec2(1., 0.);
+        }
+        if input.held("pan_right") {
+            pan -= Vec2(1., 0.);
+        }
+        if input.held("pan_up") {
+            pan += Vec2(0., 1.);
+        }
+        if input.held("pan_down") {
+            pan -= Vec2(0., 1.);
+        }
+        camera.glide(pan * (PAN_SPEED * dt));
+        camera.update(dt);
+
         // Clear and redraw the canvas
-        canvas.set_draw_color(SAGE);
-        canvas.clear();
-        draw_layout(&mut canvas, &layout, BLACK, HONEYDEW, mouse_pos);
+        grid.update(&camera, &map);
+        Renderer::clear(&mut canvas, SAGE).unwrap();
+        let preview = if editing {
+            editor.preview(camera.layout().point_to_hex(mouse_pos))
+        } else {
+            selection.preview()
+        };
+        let view = View {
+            camera: &camera,
+            grid: &grid,
+            terrain: editor.map(),
+            selection: &selection,
+            preview: &preview,
+            mouse_pos,
+        };
+        // textured terrain in the window, the screenshot below keeps the flat colors
+        let terrain = editor.map();
+        render::draw_sprites(
+            &mut canvas,
+            camera.layout(),
+            &terrain_texture,
+            &terrain_atlas,
+            camera.visible_hexes(terrain.shape()),
+            |hex| {
+                let t = editor.terrain(hex)?;
+                ALL_TERRAIN.iter().position(|a| *a == t)
+            },
+        )
+        .unwrap();
+        draw_layout(&mut canvas, &view, false);
         canvas.present();
 
+        if input.pressed("screenshot") {
+            // render the current view without the window and save it
+            let mut shot = SoftwareRenderer::new(WINDOW_WIDTH, WINDOW_HEIGHT);
+            shot.clear(SAGE).unwrap();
+            draw_layout(&mut shot, &view, true);
+            match shot.save_png(SCREENSHOT_PATH) {
+                Ok(()) => eprintln!("screenshot saved to {SCREENSHOT_PATH}"),
+                Err(e) => eprintln!("failed to save {SCREENSHOT_PATH}: {e}"),
+            }
+        }
+
         // Frame rate control
         ::std::thread::sleep(FRAME_DURATION);
     }
 }
 
-// Helper function to encapsulate drawing the layout
-fn draw_layout(
-    canvas: &mut Canvas<Window>,
-    layout: &Layout,
-    grid_color: Color,
-    hover_color: Color,
-    mouse_pos: FPoint,
-) {
+fn default_input() -> InputMap {
+    let mut input = InputMap::new();
+    for (input_key, action) in [
+        (Input::Key(Keycode::Left), "pan_left"),
+        (Input::Key(Keycode::A), "pan_left"),
+        (Input::Key(Keycode::Right), "pan_right"),
+        (Input::Key(Keycode::D), "pan_right"),
+        (Input::Key(Keycode::Up), "pan_up"),
+        (Input::Key(Keycode::W), "pan_up"),
+        (Input::Key(Keycode::Down), "pan_down"),
+        (Input::Key(Keycode::S), "pan_down"),
+        (Input::Mouse(MouseButton::Right), "drag_pan"),
+        (Input::WheelUp, "zoom_in"),
+        (Input::WheelDown, "zoom_out"),
+        (Input::Mouse(MouseButton::Left), "select"),
+        (Input::Key(Keycode::LShift), "select_line"),
+        (Input::Key(Keycode::RShift), "select_line"),
+        (Input::Key(Keycode::LCtrl), "select_rect"),
+        (Input::Key(Keycode::RCtrl), "select_rect"),
+        (Input::Key(Keycode::Backspace), "clear_selection"),
+        (Input::Key(Keycode::F1), "toggle_editor"),
+        (Input::Mouse(MouseButton::Left), "paint"),
+        (Input::Key(Keycode::Num1), "terrain_1"),
+        (Input::Key(Keycode::Num2), "terrain_2"),
+        (Input::Key(Keycode::Num3), "terrain_3"),
+        (Input::Key(Keycode::Num4), "terrain_4"),
+        (Input::Key(Keycode::B), "tool_paint"),
+        (Input::Key(Keycode::X), "tool_erase"),
+        (Input::Key(Keycode::F), "tool_fill"),
+        (Input::Key(Keycode::P), "brush_point"),
+        (Input::Key(Keycode::L), "brush_line"),
+        (Input::Key(Keycode::R), "brush_rect"),
+        (Input::Key(Keycode::H), "brush_radius"),
+        (Input::Key(Keycode::Equals), "brush_bigger"),
+        (Input::Key(Keycode::Minus), "brush_smaller"),
+        (Input::Key(Keycode::Z), "undo"),
+        (Input::Key(Keycode::Y), "redo"),
+        (Input::Key(Keycode::F5), "save_map"),
+        (Input::Key(Keycode::F9), "load_map"),
+        (Input::Key(Keycode::Tab), "toggle_orientation"),
+        (Input::Key(Keycode::F12), "screenshot"),
+        (Input::Key(Keycode::Escape), "quit"),
+    ] {
+        input.bind(input_key, action);
+    }
+    input
+}
+
+// Apply editor actions of the frame
+fn edit_map(editor: &mut Editor, input: &InputMap, hovered_hex: IHex, t: LayoutType) {
+    let terrain = match editor.tool {
+        Tool::Paint(terrain) | Tool::Fill(terrain) => terrain,
+        Tool::Erase => ALL_TERRAIN[0],
+    };
+    for (i, &choice) in ALL_TERRAIN.iter().enumerate() {
+        if input.pressed(&format!("terrain_{}", i + 1)) {
+            editor.tool = match editor.tool {
+                Tool::Fill(_) => Tool::Fill(choice),
+                _ => Tool::Paint(choice),
+            };
+        }
+    }
+    if input.pressed("tool_paint") {
+        editor.tool = Tool::Paint(terrain);
+    }
+    if input.pressed("tool_erase") {
+        editor.tool = Tool::Erase;
+    }
+    if input.pressed("tool_fill") {
+        editor.tool = Tool::Fill(terrain);
+    }
+
+    let radius = match editor.brush {
+        Brush::Radius(r) => r,
+        _ => 1,
+    };
+    for (action, brush) in [
+        ("brush_point", Brush::Point),
+        ("brush_line", Brush::Line),
+        ("brush_rect", Brush::Rect(t)),
+        ("brush_radius", Brush::Radius(radius)),
+        ("brush_bigger", Brush::Radius(radius + 1)),
+        ("brush_smaller", Brush::Radius((radius - 1).max(0))),
+    ] {
+        if input.pressed(action) {
+            editor.brush = brush;
+        }
+    }
+
+    if input.pressed("paint") {
+        editor.begin_stroke(hovered_hex);
+    }
+    if input.released("paint") {
+        editor.end_stroke();
+    }
+    if input.pressed("undo") {
+        editor.undo();
+    }
+    if input.pressed("redo") {
+        editor.redo();
+    }
+}
+
+// State needed to draw a frame
+struct View<'a> {
+    camera: &'a Camera,
+    grid: &'a GridMesh,
+    terrain: &'a TerrainMap,
+    selection: &'a Selection,
+    /// hexes of the current drag or brush
+    preview: &'a [IHex],
+    mouse_pos: Vec2,
+}
+
+// Helper function to encapsulate drawing the layout, terrain is filled with flat colors if asked
+fn draw_layout(renderer: &mut impl Renderer, view: &View, fill_terrain: bool) {
+    const HOVER: Color = Color::RGB(235, 245, 223);
+    const SELECTED: Color = Color::RGBA(250, 210, 60, 140);
+    const PREVIEW: Color = Color::RGBA(250, 250, 250, 90);
+
+    let View {
+        camera,
+        grid,
+        terrain,
+        selection,
+        preview,
+        mouse_pos,
+    } = *view;
+    let layout = camera.layout();
     let hovered_hex = layout.point_to_hex(mouse_pos);
 
-    // Draw layout grid
-    let grid_rect = Rect::new(IHEX_0, 20, 20);
-    canvas.set_draw_color(grid_color);
-    layout
-        .hex_centers(grid_rect.hex_iter())
-        .map(|center| layout.grid_corners(center))
-        .for_each(|corners| {
-            canvas.draw_flines(corners.as_slice()).unwrap();
-            canvas.draw_fline(corners[0], corners[5]).unwrap();
-        });
+    // Fill terrain under the grid
+    if fill_terrain {
+        let map = terrain.shape();
+        render::fill_hexes(renderer, layout, camera.visible_hexes(map), |hex| {
+            terrain.get(hex).copied().flatten().map(|t| t.color())
+        })
+        .unwrap();
+    }
+
+    // Tint selected hexes and the area of the current drag
+    let visible = camera.visible_rect();
+    let selected = selection
+        .hexes()
+        .iter()
+        .copied()
+        .filter(|h| visible.contains(*h));
+    render::fill_hexes(renderer, layout, selected, |_| Some(SELECTED)).unwrap();
+    render::fill_hexes(renderer, layout, preview.iter().copied(), |_| Some(PREVIEW)).unwrap();
+
+    // Draw visible part of the layout grid
+    grid.draw(renderer).unwrap();
 
     // Highlight hovered hex
     let point = Point(hovered_hex);
-    canvas.set_draw_color(hover_color);
     layout
         .hex_centers(point.hex_iter())
         .map(|center| layout.grid_corners(center))
         .for_each(|corners| {
-            canvas.draw_flines(corners.as_slice()).unwrap();
-            canvas.draw_fline(corners[0], corners[5]).unwrap();
+            let mut outline = corners.map(FPoint::from).to_vec();
+            outline.push(corners[0].into());
+            renderer.draw_lines(&outline, HOVER).unwrap();
         });
 }
+
+// Placeholder terrain in rings around the map center
+fn placeholder_terrain(map: MapShape) -> TerrainMap {
+    let center = map.center();
+    TerrainMap::from_fn(map, |hex| {
+        let band = center.distance(hex) / 8;
+        Some(ALL_TERRAIN[band as usize % ALL_TERRAIN.len()])
+    })
+}
+
+// Terrain sprites side by side in the order of `ALL_TERRAIN`, each its color with darker marks
+fn terrain_atlas<T>(creator: &TextureCreator<T>) -> Result<(Texture<'_>, SpriteAtlas), String> {
+    const CELL: u32 = 32;
+    let width = CELL * ALL_TERRAIN.len() as u32;
+    let mut surface = Surface::new(width, CELL, PixelFormatEnum::RGBA32)?;
+    for (i, &terrain) in ALL_TERRAIN.iter().enumerate() {
+        let x0 = (i as u32 * CELL) as i32;
+        let rect = |x: i32, y: i32, w: u32, h: u32| sdl2::rect::Rect::new(x0 + x, y, w, h);
+        let rows = (0..CELL as i32).step_by(8);
+        let marks: Vec<_> = match terrain {
+            // dots on every other row shifted by half of their spacing
+            Terrain::Grass => (0..16)
+                .map(|k| rect(k % 4 * 8 + k / 4 % 2 * 4 + 2, k / 4 * 8 + 2, 2, 2))
+                .collect(),
+            Terrain::Forest => [(3, 3), (19, 5), (8, 18), (22, 22)]
+                .map(|(x, y)| rect(x, y, 7, 7))
+                .to_vec(),
+            Terrain::Hills => rows.map(|y| rect(0, y + 3, CELL, 2)).collect(),
+            Terrain::Water => rows.map(|y| rect(y % 16, y + 3, CELL / 2, 2)).collect(),
+        };
+        let c = terrain.color();
+        surface.fill_rect(rect(0, 0, CELL, CELL), c)?;
+        let mark = Color::RGB(c.r - c.r / 5, c.g - c.g / 5, c.b - c.b / 5);
+        surface.fill_rects(&marks, mark)?;
+    }
+    let texture = creator
+        .create_texture_from_surface(&surface)
+        .map_err(|e| e.to_string())?;
+    Ok((texture, SpriteAtlas::new(width, CELL, CELL, CELL)))
+}
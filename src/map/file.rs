@@ -0,0 +1,642 @@
+use crate::geometry::*;
+use crate::map::text::{self, Kind, Value};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// version written by this build, older files are rejected until the format changes
+pub const MAP_VERSION: u32 = 1;
+
+/// first bytes of the binary form
+const MAGIC: &[u8; 4] = b"HXMP";
+
+/// largest number of tiles of a loaded map
+pub const MAX_MAP_AREA: usize = 1 << 24;
+
+/// all hexes of a loaded map and its tiles lie within this many hexes of the origin on both axes
+pub const MAX_MAP_COORD: i32 = 1 << 24;
+
+fn in_map_range(hex: IHex) -> bool {
+    let range = -MAX_MAP_COORD..=MAX_MAP_COORD;
+    range.contains(&hex.q()) && range.contains(&hex.r())
+}
+
+/// Named values of some tiles of the map, e.g. terrain or units.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Layer {
+    pub name: String,
+    pub tiles: Vec<(IHex, u32)>,
+}
+
+/// Everything needed to restore a map: its shape, how it is laid out and the tile layers.
+#[derive(Clone, Debug)]
+pub struct MapFile {
+    pub shape: MapShape,
+    pub layout: LayoutType,
+    pub hex_size: Vec2,
+    pub layers: Vec<Layer>,
+}
+
+/// Reasons a map can not be loaded.
+#[derive(Debug)]
+pub enum MapError {
+    Io(io::Error),
+    /// malformed text or binary data
+    Syntax {
+        line: usize,
+        message: String,
+    },
+    Version {
+        found: u32,
+        supported: u32,
+    },
+    OutOfBounds {
+        layer: String,
+        hex: IHex,
+    },
+    DuplicateTile {
+        layer: String,
+        hex: IHex,
+    },
+    /// well formed data with values that make no sense, e.g. negative sizes
+    Invalid(String),
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MapError::Io(e) => write!(f, "{e}"),
+            MapError::Syntax { line: 0, message } => write!(f, "{message}"),
+            MapError::Syntax { line, message } => write!(f, "line {line}: {message}"),
+            MapError::Version { found, supported } => {
+                write!(f, "unsupported map version {found}, expected {supported}")
+            }
+            MapError::OutOfBounds { layer, hex } => write!(
+                f,
+                "tile ({}, {}) of layer `{layer}` is outside of the map",
+                hex.q(),
+                hex.r()
+            ),
+            MapError::DuplicateTile { layer, hex } => write!(
+                f,
+                "tile ({}, {}) is repeated in layer `{layer}`",
+                hex.q(),
+                hex.r()
+            ),
+            MapError::Invalid(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for MapError {}
+
+impl From<io::Error> for MapError {
+    fn from(e: io::Error) -> Self {
+        MapError::Io(e)
+    }
+}
+
+impl MapFile {
+    pub fn new(shape: MapShape, layout: LayoutType, hex_size: Vec2) -> Self {
+        MapFile {
+            shape,
+            layout,
+            hex_size,
+            layers: Vec::new(),
+        }
+    }
+
+    pub fn layer(&self, name: &str) -> Option<&Layer> {
+        self.layers.iter().find(|l| l.name == name)
+    }
+
+    /// check sizes, that the map is not too large or far from the origin, that a rectangle
+    /// has the layout of the map and that every tile is inside of the shape and stored once per layer
+    pub fn validate(&self) -> Result<(), MapError> {
+        if let MapShape::Rect(r) = self.shape
+            && r.layout_type() != self.layout
+        {
+            return Err(MapError::Invalid(format!(
+                "{} layout of a {} rectangle",
+                layout_name(self.layout),
+                layout_name(r.layout_type())
+            )));
+        }
+        let area = match self.shape {
+            MapShape::Rect(r) => {
+                let (w, h) = (r.width(), r.height());
+                if w < 0 || h < 0 {
+                    return Err(MapError::Invalid(format!("negative map size {w}x{h}")));
+                }
+                w as i64 * h as i64
+            }
+            MapShape::Hexagon(h) => {
+                let n = h.radius();
+                if n < 0 {
+                    return Err(MapError::Invalid(format!("negative map radius {n}")));
+                }
+                3 * n as i64 * (n as i64 + 1) + 1
+            }
+        };
+        if area > MAX_MAP_AREA as i64 {
+            return Err(MapError::Invalid(format!(
+                "map of {area} tiles is larger than {MAX_MAP_AREA}"
+            )));
+        }
+        // with the origin in range the limited area keeps the corners from overflowing
+        let in_range = match self.shape {
+            MapShape::Rect(r) => {
+                let (w, h) = (r.width() as usize, r.height() as usize);
+                in_map_range(r.first())
+                    && (area == 0
+                        || [w - 1, (h - 1) * w, w * h - 1]
+                            .into_iter()
+                            .filter_map(|i| r.hex_by_ind(i))
+                            .all(in_map_range))
+            }
+            MapShape::Hexagon(h) => {
+                let (c, n) = (h.center(), h.radius());
+                in_map_range(c)
+                    && in_map_range(IHex(c.q() - n, c.r() + n))
+                    && in_map_range(IHex(c.q() + n, c.r() - n))
+            }
+        };
+        if !in_range {
+            return Err(MapError::Invalid(format!(
+                "map is not within {MAX_MAP_COORD} hexes of the origin"
+            )));
+        }
+        let (w, h) = (self.hex_size.x(), self.hex_size.y());
+        if !(w.is_finite() && h.is_finite() && w > 0. && h > 0.) {
+            return Err(MapError::Invalid(format!("bad hex size ({w}, {h})")));
+        }
+        for layer in &self.layers {
+            let mut seen = HashSet::new();
+            for &(hex, _) in &layer.tiles {
+                let layer = layer.name.clone();
+                if !in_map_range(hex) || !self.shape.contains(hex) {
+                    return Err(MapError::OutOfBounds { layer, hex });
+                }
+                if !seen.insert(hex) {
+                    return Err(MapError::DuplicateTile { layer, hex });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// read a map in either form, binary files are told apart by their first bytes
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, MapError> {
+        let data = fs::read(path)?;
+        if data.starts_with(MAGIC) {
+            return Self::from_binary(&data);
+        }
+        let text = String::from_utf8(data)
+            .map_err(|_| MapError::Invalid("map is neither binary nor text".to_string()))?;
+        Self::from_text(&text)
+    }
+
+    pub fn save_text(&self, path: impl AsRef<Path>) -> Result<(), MapError> {
+        Ok(fs::write(path, self.to_text())?)
+    }
+
+    pub fn save_binary(&self, path: impl AsRef<Path>) -> Result<(), MapError> {
+        Ok(fs::write(path, self.to_binary())?)
+    }
+
+    /// human readable form in a RON-like notation
+    pub fn to_text(&self) -> String {
+        let hex = |h: IHex| format!("({}, {})", h.q(), h.r());
+        let shape = match self.shape {
+            MapShape::Rect(r) => format!(
+                "Rect(first: {}, width: {}, height: {}, layout: {})",
+                hex(r.first()),
+                r.width(),
+                r.height(),
+                layout_name(r.layout_type())
+            ),
+            MapShape::Hexagon(h) => {
+                format!(
+                    "Hexagon(center: {}, radius: {})",
+                    hex(h.center()),
+                    h.radius()
+                )
+            }
+        };
+        let mut out = format!(
+            "HexMap(\n    version: {MAP_VERSION},\n    shape: {shape},\n    layout: {},\n    \
+             hex_size: ({:?}, {:?}),\n    layers: [\n",
+            layout_name(self.layout),
+            self.hex_size.x(),
+            self.hex_size.y()
+        );
+        for layer in &self.layers {
+            out += &format!("        Layer(\n            name: {:?},\n", layer.name);
+            out += "            tiles: [\n";
+            for &(h, value) in &layer.tiles {
+                out += &format!("                ({}, {value}),\n", hex(h));
+            }
+            out += "            ],\n        ),\n";
+        }
+        out += "    ],\n)\n";
+        out
+    }
+
+    pub fn from_text(text: &str) -> Result<Self, MapError> {
+        let root = text::parse(text)?;
+        let fields = root.fields("HexMap")?;
+        let version = fields.get("version")?.int::<u32>()?;
+        if version != MAP_VERSION {
+            return Err(MapError::Version {
+                found: version,
+                supported: MAP_VERSION,
+            });
+        }
+        let shape = fields.get("shape")?;
+        let shape = match &shape.kind {
+            Kind::Struct(name, _) if name == "Rect" => {
+                let f = shape.fields("Rect")?;
+                MapShape::Rect(Rect::with_type(
+                    f.get("first")?.hex()?,
+                    f.get("width")?.int()?,
+                    f.get("height")?.int()?,
+                    f.get("layout")?.layout()?,
+                ))
+            }
+            Kind::Struct(name, _) if name == "Hexagon" => {
+                let f = shape.fields("Hexagon")?;
+                MapShape::Hexagon(Hexagon::new(
+                    f.get("center")?.hex()?,
+                    f.get("radius")?.int()?,
+                ))
+            }
+            _ => return Err(shape.error("expected `Rect(..)` or `Hexagon(..)`")),
+        };
+        let size = fields.get("hex_size")?.tuple(2)?;
+        let mut map = MapFile::new(
+            shape,
+            fields.get("layout")?.layout()?,
+            Vec2(size[0].float()?, size[1].float()?),
+        );
+        for layer in fields.get("layers")?.list()? {
+            let f = layer.fields("Layer")?;
+            let name = f.get("name")?.string()?;
+            let tiles = f
+                .get("tiles")?
+                .list()?
+                .iter()
+                .map(|tile| {
+                    let t = tile.tuple(2)?;
+                    Ok((t[0].hex()?, t[1].int()?))
+                })
+                .collect::<Result<_, MapError>>()?;
+            map.layers.push(Layer { name, tiles });
+        }
+        map.validate()?;
+        Ok(map)
+    }
+
+    /// compact little endian form
+    pub fn to_binary(&self) -> Vec<u8> {
+        let mut out = MAGIC.to_vec();
+        let put_i32 = |out: &mut Vec<u8>, v: i32| out.extend_from_slice(&v.to_le_bytes());
+        let put_u32 = |out: &mut Vec<u8>, v: u32| out.extend_from_slice(&v.to_le_bytes());
+        put_u32(&mut out, MAP_VERSION);
+        match self.shape {
+            MapShape::Rect(r) => {
+                out.push(0);
+                put_i32(&mut out, r.first().q());
+                put_i32(&mut out, r.first().r());
+                put_i32(&mut out, r.width());
+                put_i32(&mut out, r.height());
+                out.push(r.layout_type() as u8);
+            }
+            MapShape::Hexagon(h) => {
+                out.push(1);
+                put_i32(&mut out, h.center().q());
+                put_i32(&mut out, h.center().r());
+                put_i32(&mut out, h.radius());
+            }
+        }
+        out.push(self.layout as u8);
+        out.extend_from_slice(&self.hex_size.x().to_le_bytes());
+        out.extend_from_slice(&self.hex_size.y().to_le_bytes());
+        put_u32(&mut out, self.layers.len() as u32);
+        for layer in &self.layers {
+            put_u32(&mut out, layer.name.len() as u32);
+            out.extend_from_slice(layer.name.as_bytes());
+            put_u32(&mut out, layer.tiles.len() as u32);
+            for &(hex, value) in &layer.tiles {
+                put_i32(&mut out, hex.q());
+                put_i32(&mut out, hex.r());
+                put_u32(&mut out, value);
+            }
+        }
+        out
+    }
+
+    pub fn from_binary(data: &[u8]) -> Result<Self, MapError> {
+        let mut r = Reader { data, pos: 0 };
+        if r.take(4)? != MAGIC {
+            return Err(r.error("not a binary map"));
+        }
+        let version = r.u32()?;
+        if version != MAP_VERSION {
+            return Err(MapError::Version {
+                found: version,
+                supported: MAP_VERSION,
+            });
+        }
+        let shape = match r.u8()? {
+            0 => {
+                let h0 = IHex(r.i32()?, r.i32()?);
+                let (w, h) = (r.i32()?, r.i32()?);
+                MapShape::Rect(Rect::with_type(h0, w, h, r.layout()?))
+            }
+            1 => MapShape::Hexagon(Hexagon::new(IHex(r.i32()?, r.i32()?), r.i32()?)),
+            tag => return Err(r.error(&format!("unknown shape {tag}"))),
+        };
+        let layout = r.layout()?;
+        let hex_size = Vec2(r.f32()?, r.f32()?);
+        let mut map = MapFile::new(shape, layout, hex_size);
+        for _ in 0..r.u32()? {
+            let len = r.u32()? as usize;
+            let name = String::from_utf8(r.take(len)?.to_vec())
+                .map_err(|_| r.error("layer name is not UTF-8"))?;
+            let count = r.u32()? as usize;
+            // every tile takes 12 bytes, do not trust the count with the allocation
+            let mut tiles = Vec::with_capacity(count.min(r.remaining() / 12));
+            for _ in 0..count {
+                tiles.push((IHex(r.i32()?, r.i32()?), r.u32()?));
+            }
+            map.layers.push(Layer { name, tiles });
+        }
+        if r.remaining() > 0 {
+            return Err(r.error("unexpected data after the last layer"));
+        }
+        map.validate()?;
+        Ok(map)
+    }
+}
+
+fn layout_name(t: LayoutType) -> &'static str {
+    match t {
+        LayoutType::Pointy => "Pointy",
+        LayoutType::Flat => "Flat",
+    }
+}
+
+/// sequential access to binary data, errors report the byte offset
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn error(&self, message: &str) -> MapError {
+        MapError::Syntax {
+            line: 0,
+            message: format!("byte {}: {message}", self.pos),
+        }
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], MapError> {
+        if n > self.remaining() {
+            return Err(self.error("unexpected end of data"));
+        }
+        self.pos += n;
+        Ok(&self.data[self.pos - n..self.pos])
+    }
+
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N], MapError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn u8(&mut self) -> Result<u8, MapError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, MapError> {
+        Ok(u32::from_le_bytes(self.bytes()?))
+    }
+
+    fn i32(&mut self) -> Result<i32, MapError> {
+        Ok(i32::from_le_bytes(self.bytes()?))
+    }
+
+    fn f32(&mut self) -> Result<f32, MapError> {
+        Ok(f32::from_le_bytes(self.bytes()?))
+    }
+
+    fn layout(&mut self) -> Result<LayoutType, MapError> {
+        match self.u8()? {
+            0 => Ok(LayoutType::Flat),
+            1 => Ok(LayoutType::Pointy),
+            t => Err(self.error(&format!("unknown layout {t}"))),
+        }
+    }
+}
+
+impl Value {
+    fn hex(&self) -> Result<IHex, MapError> {
+        let t = self.tuple(2)?;
+        Ok(IHex(t[0].int()?, t[1].int()?))
+    }
+
+    fn layout(&self) -> Result<LayoutType, MapError> {
+        match &self.kind {
+            Kind::Ident(s) if s == "Pointy" => Ok(LayoutType::Pointy),
+            Kind::Ident(s) if s == "Flat" => Ok(LayoutType::Flat),
+            _ => Err(self.error("expected `Pointy` or `Flat`")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::*;
+    use crate::map::{Layer, MAP_VERSION, MAX_MAP_COORD, MapError, MapFile};
+
+    fn sample() -> MapFile {
+        let rect = Rect::with_type(IHex(-1, 2), 4, 3, LayoutType::Flat);
+        let mut map = MapFile::new(MapShape::Rect(rect), LayoutType::Flat, Vec2(16., 12.5));
+        map.layers.push(Layer {
+            name: "terrain".to_string(),
+            tiles: rect.hex_iter().zip(0..).collect(),
+        });
+        map.layers.push(Layer {
+            name: "units \"a\"".to_string(),
+            tiles: vec![(IHex(0, 2), 7)],
+        });
+        map
+    }
+
+    fn assert_same(a: &MapFile, b: &MapFile) {
+        assert_eq!(a.layers, b.layers);
+        assert_eq!(a.layout, b.layout);
+        assert_eq!(a.hex_size, b.hex_size);
+        assert_eq!(a.shape.area(), b.shape.area());
+        assert!(a.shape.hex_iter().eq(b.shape.hex_iter()));
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let map = sample();
+        let text = map.to_text();
+        assert_same(&map, &MapFile::from_text(&text).unwrap());
+        let hexagon = MapFile::new(
+            MapShape::Hexagon(Hexagon::new(IHex(3, -1), 2)),
+            LayoutType::Pointy,
+            Vec2(10., 10.),
+        );
+        assert_same(&hexagon, &MapFile::from_text(&hexagon.to_text()).unwrap());
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let map = sample();
+        let data = map.to_binary();
+        assert_same(&map, &MapFile::from_binary(&data).unwrap());
+        // the text form is much larger
+        assert!(data.len() * 2 < map.to_text().len());
+        for len in 0..data.len() {
+            assert!(MapFile::from_binary(&data[..len]).is_err());
+        }
+    }
+
+    #[test]
+    fn hand_written_text() {
+        let text = r#"
+            // comments and trailing commas are fine
+            HexMap(
+                version: 1,
+                layout: Pointy,
+                hex_size: (16, 16.0),
+                shape: Hexagon(center: (0, 0), radius: 1),
+                layers: [Layer(name: "terrain", tiles: [((0, 0), 1), ((1, -1), 2),])],
+            )
+        "#;
+        let map = MapFile::from_text(text).unwrap();
+        assert_eq!(map.layer("terrain").unwrap().tiles.len(), 2);
+        assert!(map.layer("units").is_none());
+    }
+
+    #[test]
+    fn validation_errors() {
+        let mut map = sample();
+        map.layers[1].tiles.push((IHex(10, 10), 1));
+        let err = MapFile::from_text(&map.to_text()).unwrap_err();
+        assert!(matches!(
+            err,
+            MapError::OutOfBounds {
+                hex: IHex(10, 10),
+                ..
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "tile (10, 10) of layer `units \"a\"` is outside of the map"
+        );
+        assert!(matches!(
+            MapFile::from_binary(&map.to_binary()),
+            Err(MapError::OutOfBounds { .. })
+        ));
+        map.layers[1].tiles = vec![(IHex(0, 2), 1), (IHex(0, 2), 2)];
+        assert!(matches!(
+            MapFile::from_text(&map.to_text()),
+            Err(MapError::DuplicateTile { .. })
+        ));
+
+        let text = sample().to_text().replace("version: 1", "version: 9");
+        let err = MapFile::from_text(&text).unwrap_err();
+        assert!(matches!(
+            err,
+            MapError::Version {
+                found: 9,
+                supported: MAP_VERSION
+            }
+        ));
+        let mut data = sample().to_binary();
+        data[4] = 2;
+        assert!(matches!(
+            MapFile::from_binary(&data),
+            Err(MapError::Version { found: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn syntax_errors() {
+        let text = sample().to_text();
+        let err = MapFile::from_text(&text.replace("width: 4", "width: four")).unwrap_err();
+        assert_eq!(err.to_string(), "line 3: expected an integer");
+        let err = MapFile::from_text(&text.replace("hex_size", "size")).unwrap_err();
+        assert_eq!(err.to_string(), "line 1: missing field `hex_size`");
+        let err = MapFile::from_text(&text.replace("Flat", "Round")).unwrap_err();
+        assert!(err.to_string().contains("expected `Pointy` or `Flat`"));
+        let err =
+            MapFile::from_text(&text.replace("layout: Flat,", "layout: Pointy,")).unwrap_err();
+        assert!(matches!(err, MapError::Invalid(_)));
+        assert_eq!(err.to_string(), "Pointy layout of a Flat rectangle");
+        assert!(MapFile::from_text("HexMap(version: 1").is_err());
+        assert!(MapFile::from_text("").is_err());
+        assert!(MapFile::from_text(&text.replace("height: 3", "height: -3")).is_err());
+    }
+
+    #[test]
+    fn exponent_hex_size() {
+        for size in [Vec2(1e-7, 2.5e-9), Vec2(3e38, f32::MAX)] {
+            let mut map = sample();
+            map.hex_size = size;
+            let text = map.to_text();
+            assert!(text.contains("e-") || text.contains("e38"), "{text}");
+            assert_same(&map, &MapFile::from_text(&text).unwrap());
+        }
+        let text = sample().to_text();
+        let map = MapFile::from_text(&text.replace("(16.0, 12.5)", "(1.6E+1, 125e-1)")).unwrap();
+        assert_eq!(map.hex_size, Vec2(16., 12.5));
+    }
+
+    #[test]
+    fn nesting_limit() {
+        let err = MapFile::from_text(&"[".repeat(100_000)).unwrap_err();
+        assert!(matches!(err, MapError::Invalid(_)));
+        assert_eq!(err.to_string(), "line 1: values are nested deeper than 64");
+        let text = sample().to_text();
+        let nested = format!("{}0{}", "(".repeat(60), ")".repeat(60));
+        let err = MapFile::from_text(&text.replace("version: 1", &format!("version: {nested}")))
+            .unwrap_err();
+        assert!(matches!(err, MapError::Syntax { .. }));
+    }
+
+    #[test]
+    fn size_limits() {
+        let text = sample().to_text();
+        let err = MapFile::from_text(&text.replace("width: 4", "width: 2000000000")).unwrap_err();
+        assert!(matches!(err, MapError::Invalid(_)));
+        assert_eq!(
+            err.to_string(),
+            "map of 6000000000 tiles is larger than 16777216"
+        );
+        let far = text.replace("first: (-1, 2)", "first: (2147483000, 2)");
+        let err = MapFile::from_text(&far).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "map is not within 16777216 hexes of the origin"
+        );
+        let hexagon = MapFile::new(
+            MapShape::Hexagon(Hexagon::new(IHex(0, MAX_MAP_COORD), 1)),
+            LayoutType::Pointy,
+            Vec2(10., 10.),
+        );
+        assert!(matches!(hexagon.validate(), Err(MapError::Invalid(_))));
+        let mut map = sample();
+        map.layers[1].tiles.push((IHex(i32::MIN, i32::MAX), 1));
+        assert!(matches!(map.validate(), Err(MapError::OutOfBounds { .. })));
+    }
+}
@@ -0,0 +1,163 @@
+use crate::geometry::{IHex, Shape};
+use std::ops::{Index, IndexMut};
+
+/// Dense storage of one value per tile of a shape, stored in the shape's tile order.
+#[derive(Clone, Debug)]
+pub struct HexMap<S: Shape, T> {
+    shape: S,
+    values: Vec<T>,
+}
+
+impl<S: Shape, T: Clone> HexMap<S, T> {
+    /// map with every tile set to `value`
+    pub fn new(shape: S, value: T) -> Self {
+        let values = vec![value; shape.area()];
+        HexMap { shape, values }
+    }
+
+    /// change the shape of the map, tiles outside of the old shape are set to `value`
+    pub fn resize(&mut self, shape: S, value: T) {
+        self.reshape(shape, |_| value.clone());
+    }
+}
+
+impl<S: Shape, T> HexMap<S, T> {
+    /// map with every tile initialized by `f`
+    pub fn from_fn(shape: S, f: impl FnMut(IHex) -> T) -> Self {
+        let values = shape.hex_iter().map(f).collect();
+        HexMap { shape, values }
+    }
+
+    pub fn shape(&self) -> &S {
+        &self.shape
+    }
+
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn contains(&self, hex: IHex) -> bool {
+        self.shape.contains(hex)
+    }
+
+    pub fn get(&self, hex: IHex) -> Option<&T> {
+        self.shape.ind_by_hex(hex).map(|i| &self.values[i])
+    }
+
+    pub fn get_mut(&mut self, hex: IHex) -> Option<&mut T> {
+        self.shape.ind_by_hex(hex).map(|i| &mut self.values[i])
+    }
+
+    /// replace value at the hex, returns previous one or None when hex is outside of the map
+    pub fn set(&mut self, hex: IHex, value: T) -> Option<T> {
+        self.get_mut(hex).map(|v| std::mem::replace(v, value))
+    }
+
+    /// iterator of tiles and values in the shape order
+    pub fn iter(&self) -> impl Iterator<Item = (IHex, &T)> {
+        self.shape.hex_iter().zip(self.values.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (IHex, &mut T)> {
+        self.shape.hex_iter().zip(self.values.iter_mut())
+    }
+
+    /// change the shape of the map, values of tiles present in both shapes are kept,
+    /// new tiles are initialized by `fill`
+    pub fn reshape(&mut self, shape: S, mut fill: impl FnMut(IHex) -> T) {
+        let mut old = std::mem::take(&mut self.values)
+            .into_iter()
+            .map(Some)
+            .collect::<Vec<Option<T>>>();
+        self.values = shape
+            .hex_iter()
+            .map(|hex| {
+                self.shape
+                    .ind_by_hex(hex)
+                    .and_then(|i| old[i].take())
+                    .unwrap_or_else(|| fill(hex))
+            })
+            .collect();
+        self.shape = shape;
+    }
+}
+
+impl<S: Shape, T> Index<IHex> for HexMap<S, T> {
+    type Output = T;
+
+    fn index(&self, hex: IHex) -> &Self::Output {
+        match self.get(hex) {
+            Some(v) => v,
+            None => panic!("hex {hex:?} is outside of the map"),
+        }
+    }
+}
+
+impl<S: Shape, T> IndexMut<IHex> for HexMap<S, T> {
+    fn index_mut(&mut self, hex: IHex) -> &mut Self::Output {
+        match self.get_mut(hex) {
+            Some(v) => v,
+            None => panic!("hex {hex:?} is outside of the map"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::{Hexagon, IHex, Rect, Shape};
+    use crate::map::HexMap;
+
+    #[test]
+    fn get_set() {
+        let mut m = HexMap::new(Rect::new(IHex(0, 0), 4, 4), 0);
+        assert_eq!(m.len(), 16);
+        assert_eq!(m.get(IHex(1, 1)), Some(&0));
+        assert_eq!(m.get(IHex(-5, 0)), None);
+        m[IHex(1, 1)] = 7;
+        assert_eq!(m.set(IHex(1, 1), 8), Some(7));
+        assert_eq!(m.set(IHex(-5, 0), 8), None);
+        *m.get_mut(IHex(2, 0)).unwrap() += 3;
+        assert_eq!(m[IHex(1, 1)], 8);
+        assert_eq!(m[IHex(2, 0)], 3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_outside() {
+        let m = HexMap::new(Hexagon::new(IHex(0, 0), 1), 0);
+        let _ = m[IHex(2, 0)];
+    }
+
+    #[test]
+    fn iter_in_shape_order() {
+        let shape = Hexagon::new(IHex(1, 1), 2);
+        let m = HexMap::from_fn(shape, |h| h.distance(IHex(1, 1)));
+        for ((hex, v), want) in m.iter().zip(shape.hex_iter()) {
+            assert_eq!(hex, want);
+            assert_eq!(*v, hex.distance(IHex(1, 1)));
+        }
+        assert_eq!(m.iter().count(), shape.area());
+    }
+
+    #[test]
+    fn reshape_keeps_values() {
+        let mut m = HexMap::from_fn(Rect::new(IHex(0, 0), 3, 3), Some);
+        m.resize(Rect::new(IHex(-1, 0), 5, 2), None);
+        assert_eq!(m.len(), 10);
+        for (hex, v) in m.iter() {
+            if Rect::new(IHex(0, 0), 3, 3).contains(hex) {
+                assert_eq!(*v, Some(hex));
+            } else {
+                assert_eq!(*v, None);
+            }
+        }
+    }
+}
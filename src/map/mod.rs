@@ -0,0 +1,8 @@
+mod file;
+mod hexmap;
+mod text;
+mod tiled;
+
+pub use file::*;
+pub use hexmap::*;
+pub use tiled::*;
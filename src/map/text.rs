@@ -0,0 +1,351 @@
+use crate::map::MapError;
+
+/// value of a map text with the line it starts on
+///
+/// The RON-like text form and Tiled's JSON share the tokens and the value
+/// grammar, `true`, `false` and `null` of JSON are read as identifiers.
+#[derive(Debug)]
+pub struct Value {
+    pub line: usize,
+    pub kind: Kind,
+}
+
+#[derive(Debug)]
+pub enum Kind {
+    /// numbers keep their text to be parsed into the expected type
+    Number(String),
+    Str(String),
+    Ident(String),
+    Tuple(Vec<Value>),
+    List(Vec<Value>),
+    Struct(String, Vec<(String, Value)>),
+    /// JSON object
+    Object(Vec<(String, Value)>),
+}
+
+/// fields of a struct value
+pub struct Fields<'a> {
+    value: &'a Value,
+    fields: &'a [(String, Value)],
+}
+
+impl<'a> Fields<'a> {
+    pub fn get(&self, name: &str) -> Result<&'a Value, MapError> {
+        self.fields
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v)
+            .ok_or_else(|| self.value.error(&format!("missing field `{name}`")))
+    }
+}
+
+impl Value {
+    pub fn error(&self, message: &str) -> MapError {
+        MapError::Syntax {
+            line: self.line,
+            message: message.to_string(),
+        }
+    }
+
+    pub fn fields(&self, name: &str) -> Result<Fields<'_>, MapError> {
+        match &self.kind {
+            Kind::Struct(n, fields) if n == name => Ok(Fields {
+                value: self,
+                fields,
+            }),
+            _ => Err(self.error(&format!("expected `{name}(..)`"))),
+        }
+    }
+
+    /// member of an object, None when missing or null
+    pub fn opt(&self, key: &str) -> Option<&Value> {
+        match &self.kind {
+            Kind::Object(members) => members
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v)
+                .filter(|v| !matches!(&v.kind, Kind::Ident(s) if s == "null")),
+            _ => None,
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Result<&Value, MapError> {
+        self.opt(key)
+            .ok_or_else(|| self.error(&format!("missing member `{key}`")))
+    }
+
+    pub fn boolean(&self) -> Result<bool, MapError> {
+        match &self.kind {
+            Kind::Ident(s) if s == "true" => Ok(true),
+            Kind::Ident(s) if s == "false" => Ok(false),
+            _ => Err(self.error("expected `true` or `false`")),
+        }
+    }
+
+    pub fn int<T: std::str::FromStr>(&self) -> Result<T, MapError> {
+        match &self.kind {
+            Kind::Number(n) => n
+                .parse()
+                .map_err(|_| self.error(&format!("`{n}` is not a valid integer here"))),
+            _ => Err(self.error("expected an integer")),
+        }
+    }
+
+    pub fn float(&self) -> Result<f32, MapError> {
+        match &self.kind {
+            Kind::Number(n) => n
+                .parse()
+                .map_err(|_| self.error(&format!("`{n}` is not a number"))),
+            _ => Err(self.error("expected a number")),
+        }
+    }
+
+    pub fn string(&self) -> Result<String, MapError> {
+        match &self.kind {
+            Kind::Str(s) => Ok(s.clone()),
+            _ => Err(self.error("expected a string")),
+        }
+    }
+
+    pub fn tuple(&self, len: usize) -> Result<&[Value], MapError> {
+        match &self.kind {
+            Kind::Tuple(items) if items.len() == len => Ok(items),
+            _ => Err(self.error(&format!("expected a tuple of {len} values"))),
+        }
+    }
+
+    pub fn list(&self) -> Result<&[Value], MapError> {
+        match &self.kind {
+            Kind::List(items) => Ok(items),
+            _ => Err(self.error("expected a list `[..]`")),
+        }
+    }
+}
+
+/// parses the whole text as a single value
+pub fn parse(text: &str) -> Result<Value, MapError> {
+    Parser::new(text)?.document()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(String),
+    Str(String),
+    Ident(String),
+    Punct(char),
+}
+
+/// deepest nesting of brackets, deeper text is rejected before it overflows the stack
+const MAX_DEPTH: usize = 64;
+
+/// recursive descent parser over the tokens of the text
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    /// brackets open at the current token
+    depth: usize,
+}
+
+impl Parser {
+    fn new(text: &str) -> Result<Self, MapError> {
+        let mut tokens = Vec::new();
+        let mut chars = text.chars().peekable();
+        let mut line = 1;
+        let syntax = |line, message: String| MapError::Syntax { line, message };
+        while let Some(&c) = chars.peek() {
+            match c {
+                '\n' => {
+                    line += 1;
+                    chars.next();
+                }
+                c if c.is_whitespace() => {
+                    chars.next();
+                }
+                // `//` comments run to the end of the line
+                '/' => {
+                    chars.next();
+                    if chars.next() != Some('/') {
+                        return Err(syntax(line, "expected `//` comment".into()));
+                    }
+                    while chars.next_if(|&c| c != '\n').is_some() {}
+                }
+                '(' | ')' | '[' | ']' | '{' | '}' | ',' | ':' => {
+                    tokens.push((line, Token::Punct(c)));
+                    chars.next();
+                }
+                '"' => {
+                    chars.next();
+                    let mut s = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('"') => break,
+                            Some('\\') => match chars.next() {
+                                Some('n') => s.push('\n'),
+                                Some('t') => s.push('\t'),
+                                Some('r') => s.push('\r'),
+                                Some('b') => s.push('\u{8}'),
+                                Some('f') => s.push('\u{c}'),
+                                Some('u') => {
+                                    let code: String = chars.by_ref().take(4).collect();
+                                    // surrogate pairs are not needed for layer and object names
+                                    let c = u32::from_str_radix(&code, 16)
+                                        .ok()
+                                        .and_then(char::from_u32)
+                                        .unwrap_or(char::REPLACEMENT_CHARACTER);
+                                    s.push(c);
+                                }
+                                Some(c @ ('"' | '\\' | '/')) => s.push(c),
+                                _ => return Err(syntax(line, "bad escape in string".into())),
+                            },
+                            Some('\n') | None => {
+                                return Err(syntax(line, "unterminated string".into()));
+                            }
+                            Some(c) => s.push(c),
+                        }
+                    }
+                    tokens.push((line, Token::Str(s)));
+                }
+                c if c == '-' || c.is_ascii_digit() => {
+                    let mut s = String::from(c);
+                    chars.next();
+                    while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '.') {
+                        s.push(c);
+                        // signed exponents, e.g. `1e-7` as written by `{:?}`
+                        if matches!(c, 'e' | 'E')
+                            && let Some(sign) = chars.next_if(|c| matches!(c, '+' | '-'))
+                        {
+                            s.push(sign);
+                        }
+                    }
+                    tokens.push((line, Token::Number(s)));
+                }
+                c if c.is_alphabetic() || c == '_' => {
+                    let mut s = String::new();
+                    while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || *c == '_') {
+                        s.push(c);
+                    }
+                    tokens.push((line, Token::Ident(s)));
+                }
+                c => return Err(syntax(line, format!("unexpected character `{c}`"))),
+            }
+        }
+        Ok(Parser {
+            tokens,
+            pos: 0,
+            depth: 0,
+        })
+    }
+
+    fn line(&self) -> usize {
+        match self.tokens.get(self.pos).or(self.tokens.last()) {
+            Some((line, _)) => *line,
+            None => 1,
+        }
+    }
+
+    fn error(&self, message: &str) -> MapError {
+        MapError::Syntax {
+            line: self.line(),
+            message: message.to_string(),
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, t)| t)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let (_, token) = self.tokens.get(self.pos)?.clone();
+        self.pos += 1;
+        Some(token)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(&Token::Punct(c));
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), MapError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{c}`")))
+        }
+    }
+
+    /// the whole text is a single value
+    fn document(mut self) -> Result<Value, MapError> {
+        let value = self.value()?;
+        if self.peek().is_some() {
+            return Err(self.error("unexpected text after the map"));
+        }
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Value, MapError> {
+        let line = self.line();
+        let kind = match self.next() {
+            Some(Token::Number(n)) => Kind::Number(n),
+            Some(Token::Str(s)) => Kind::Str(s),
+            Some(Token::Ident(name)) if self.eat('(') => {
+                let fields = self.items(')', |p| {
+                    let Some(Token::Ident(key)) = p.next() else {
+                        return Err(p.error("expected a field name"));
+                    };
+                    p.expect(':')?;
+                    Ok((key, p.value()?))
+                })?;
+                Kind::Struct(name, fields)
+            }
+            Some(Token::Ident(name)) => Kind::Ident(name),
+            Some(Token::Punct('(')) => Kind::Tuple(self.items(')', Self::value)?),
+            Some(Token::Punct('[')) => Kind::List(self.items(']', Self::value)?),
+            Some(Token::Punct('{')) => {
+                let members = self.items('}', |p| {
+                    let Some(Token::Str(key)) = p.next() else {
+                        return Err(p.error("expected a member name"));
+                    };
+                    p.expect(':')?;
+                    Ok((key, p.value()?))
+                })?;
+                Kind::Object(members)
+            }
+            Some(_) => {
+                return Err(MapError::Syntax {
+                    line,
+                    message: "expected a value".to_string(),
+                });
+            }
+            None => return Err(self.error("unexpected end of text")),
+        };
+        Ok(Value { line, kind })
+    }
+
+    /// comma separated items up to the closing bracket, a trailing comma is allowed
+    fn items<T>(
+        &mut self,
+        close: char,
+        mut item: impl FnMut(&mut Self) -> Result<T, MapError>,
+    ) -> Result<Vec<T>, MapError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(MapError::Invalid(format!(
+                "line {}: values are nested deeper than {MAX_DEPTH}",
+                self.line()
+            )));
+        }
+        let mut items = Vec::new();
+        while !self.eat(close) {
+            items.push(item(self)?);
+            if !self.eat(',') {
+                self.expect(close)?;
+                break;
+            }
+        }
+        self.depth -= 1;
+        Ok(items)
+    }
+}
@@ -0,0 +1,447 @@
+use crate::geometry::*;
+use crate::map::text::{self, Value};
+use crate::map::{Layer, MAX_MAP_COORD, MapError, MapFile};
+use std::fs;
+use std::path::Path;
+
+/// flip and rotation flags in the high bits of a Tiled global tile id
+const GID_FLAGS: u32 = 0xf000_0000;
+
+/// Hexagonal map exported from Tiled as JSON (.tmj).
+///
+/// Tile layers become layers of `map` holding the global tile ids without the
+/// flip flags, empty tiles are left out. Tiles are placed by converting their
+/// Tiled column and row with `offset`, and `layout` puts every hex center on
+/// the pixel center of its Tiled tile.
+#[derive(Clone, Debug)]
+pub struct TiledMap {
+    pub map: MapFile,
+    pub layout: Layout,
+    pub offset: OffsetType,
+    pub object_layers: Vec<ObjectLayer>,
+}
+
+/// Objects of a Tiled object layer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjectLayer {
+    pub name: String,
+    pub objects: Vec<TiledObject>,
+}
+
+/// Object placed in Tiled, located by the center of its bounding box.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TiledObject {
+    pub id: u32,
+    pub name: String,
+    /// `class`, or `type` of maps saved by Tiled before 1.9
+    pub class: String,
+    /// center in Tiled pixels
+    pub position: Vec2,
+    /// hex under the center
+    pub hex: IHex,
+    /// global tile id of tile objects
+    pub gid: Option<u32>,
+}
+
+impl TiledMap {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, MapError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, MapError> {
+        let root = text::parse(text)?;
+        if root.get("orientation")?.string()? != "hexagonal" {
+            return Err(root.error("only hexagonal maps can be imported"));
+        }
+        if root.opt("infinite").map(Value::boolean).transpose()? == Some(true) {
+            return Err(root.error("infinite maps are not supported"));
+        }
+        let stagger_axis = root.get("staggeraxis")?;
+        let stagger_index = root.get("staggerindex")?;
+        let offset = match (
+            stagger_axis.string()?.as_str(),
+            stagger_index.string()?.as_str(),
+        ) {
+            ("y", "odd") => OffsetType::OddR,
+            ("y", "even") => OffsetType::EvenR,
+            ("x", "odd") => OffsetType::OddQ,
+            ("x", "even") => OffsetType::EvenQ,
+            ("x" | "y", _) => return Err(stagger_index.error("expected `odd` or `even`")),
+            _ => return Err(stagger_axis.error("expected `x` or `y`")),
+        };
+        let t = offset.layout_type();
+
+        let width = size(root.get("width")?)?;
+        let height = size(root.get("height")?)?;
+        let tile_w: f32 = root.get("tilewidth")?.float()?;
+        let tile_h: f32 = root.get("tileheight")?.float()?;
+        let side: f32 = root.get("hexsidelength")?.float()?;
+
+        // Tiled steps by half a tile plus half a side along the staggered axis,
+        // the hex size is chosen so that the centers of both grids match
+        let hex_size = match t {
+            LayoutType::Pointy => Vec2(tile_w / 3f32.sqrt(), (tile_h + side) / 3.),
+            LayoutType::Flat => Vec2((tile_w + side) / 3., tile_h / 3f32.sqrt()),
+        };
+        // the shoved rows or columns of even maps start with the first one
+        let origin = match offset {
+            OffsetType::OddR | OffsetType::OddQ => Vec2(tile_w / 2., tile_h / 2.),
+            OffsetType::EvenR => Vec2(tile_w, tile_h / 2.),
+            OffsetType::EvenQ => Vec2(tile_w / 2., tile_h),
+        };
+        let layout = Layout::new(t.orientation(), hex_size, origin);
+
+        // a Rect starts with an unshoved row or column, even maps get one more in front
+        let shape = match offset {
+            OffsetType::OddR | OffsetType::OddQ => Rect::with_type(IHEX_0, width, height, t),
+            OffsetType::EvenR => {
+                let h0 = OffsetCoord::new(0, -1).to_hex(offset);
+                Rect::with_type(h0, width, height + 1, t)
+            }
+            OffsetType::EvenQ => {
+                let h0 = OffsetCoord::new(-1, 0).to_hex(offset);
+                Rect::with_type(h0, width + 1, height, t)
+            }
+        };
+
+        let mut tiled = TiledMap {
+            map: MapFile::new(MapShape::Rect(shape), t, hex_size),
+            layout,
+            offset,
+            object_layers: Vec::new(),
+        };
+        tiled.add_layers(root.get("layers")?, "")?;
+        tiled.map.validate()?;
+        Ok(tiled)
+    }
+
+    /// hex of the tile in the given Tiled column and row
+    pub fn tile_hex(&self, col: i32, row: i32) -> IHex {
+        OffsetCoord::new(col, row).to_hex(self.offset)
+    }
+
+    /// layers of groups are named by the path through the groups, e.g. `group/layer`
+    fn add_layers(&mut self, layers: &Value, prefix: &str) -> Result<(), MapError> {
+        for layer in layers.list()? {
+            let name = format!("{prefix}{}", layer.get("name")?.string()?);
+            match layer.get("type")?.string()?.as_str() {
+                "tilelayer" => {
+                    let tiles = self.tiles(layer, &name)?;
+                    self.map.layers.push(Layer { name, tiles });
+                }
+                "objectgroup" => {
+                    let objects = layer
+                        .get("objects")?
+                        .list()?
+                        .iter()
+                        .map(|o| self.object(o))
+                        .collect::<Result<_, _>>()?;
+                    self.object_layers.push(ObjectLayer { name, objects });
+                }
+                "group" => self.add_layers(layer.get("layers")?, &format!("{name}/"))?,
+                // image layers have nothing to place on the hexes
+                "imagelayer" => {}
+                other => return Err(layer.error(&format!("unknown layer type `{other}`"))),
+            }
+        }
+        Ok(())
+    }
+
+    fn tiles(&self, layer: &Value, name: &str) -> Result<Vec<(IHex, u32)>, MapError> {
+        let width = size(layer.get("width")?)?;
+        let height = size(layer.get("height")?)?;
+        let x = layer.opt("x").map_or(Ok(0), coordinate)?;
+        let y = layer.opt("y").map_or(Ok(0), coordinate)?;
+        let data = layer.get("data")?;
+        let gids = match layer
+            .opt("encoding")
+            .map(Value::string)
+            .transpose()?
+            .as_deref()
+        {
+            None | Some("csv") => data
+                .list()?
+                .iter()
+                .map(Value::int)
+                .collect::<Result<Vec<u32>, _>>()?,
+            Some("base64") => {
+                if let Some(c) = layer.opt("compression")
+                    && !c.string()?.is_empty()
+                {
+                    return Err(c.error("compressed tile data is not supported"));
+                }
+                let bytes = base64(&data.string()?).ok_or_else(|| data.error("bad base64 data"))?;
+                bytes
+                    .chunks_exact(4)
+                    .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+                    .collect()
+            }
+            Some(other) => return Err(layer.error(&format!("unknown encoding `{other}`"))),
+        };
+        if (width as usize).checked_mul(height as usize) != Some(gids.len()) {
+            return Err(data.error(&format!(
+                "layer `{name}` has {} tiles instead of {width}x{height}",
+                gids.len()
+            )));
+        }
+        let tiles = gids
+            .into_iter()
+            .enumerate()
+            .filter(|&(_, gid)| gid & !GID_FLAGS != 0)
+            .map(|(i, gid)| {
+                let (col, row) = (i as i32 % width, i as i32 / width);
+                (self.tile_hex(x + col, y + row), gid & !GID_FLAGS)
+            })
+            .collect();
+        Ok(tiles)
+    }
+
+    fn object(&self, object: &Value) -> Result<TiledObject, MapError> {
+        let float = |key| object.opt(key).map_or(Ok(0.), Value::float);
+        let string = |key| object.opt(key).map_or(Ok(String::new()), Value::string);
+        let (x, y, w, h) = (float("x")?, float("y")?, float("width")?, float("height")?);
+        let gid = object
+            .opt("gid")
+            .map(Value::int::<u32>)
+            .transpose()?
+            .map(|gid| gid & !GID_FLAGS);
+        // tile objects are anchored at their bottom left corner, others at the top left
+        let position = match gid {
+            Some(_) => Vec2(x + w / 2., y - h / 2.),
+            None => Vec2(x + w / 2., y + h / 2.),
+        };
+        let class = match object.opt("class") {
+            Some(c) => c.string()?,
+            None => string("type")?,
+        };
+        Ok(TiledObject {
+            id: object.get("id")?.int()?,
+            name: string("name")?,
+            class,
+            position,
+            hex: self.layout.point_to_hex(position),
+            gid,
+        })
+    }
+}
+
+/// width or height in tiles, limited like map coordinates so that tile math can not overflow
+fn size(json: &Value) -> Result<i32, MapError> {
+    let n: i32 = json.int()?;
+    if !(0..=MAX_MAP_COORD).contains(&n) {
+        return Err(json.error(&format!("size {n} is outside of 0..={MAX_MAP_COORD}")));
+    }
+    Ok(n)
+}
+
+/// tile offset of a layer
+fn coordinate(json: &Value) -> Result<i32, MapError> {
+    let n: i32 = json.int()?;
+    if !(-MAX_MAP_COORD..=MAX_MAP_COORD).contains(&n) {
+        return Err(json.error(&format!("offset {n} is outside of ±{MAX_MAP_COORD}")));
+    }
+    Ok(n)
+}
+
+/// standard base64 with padding, None on any other character
+fn base64(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let (mut acc, mut bits) = (0u32, 0);
+    for c in text
+        .bytes()
+        .filter(|c| !c.is_ascii_whitespace() && *c != b'=')
+    {
+        let v = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        acc = acc << 6 | v as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((acc >> bits) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::*;
+    use crate::map::{MapError, TiledMap};
+
+    /// 3x2 map with a tile layer in a group and an object layer
+    fn tmj(axis: &str, index: &str) -> String {
+        format!(
+            r#"{{
+  "compressionlevel": -1,
+  "height": 2, "width": 3,
+  "hexsidelength": 16,
+  "infinite": false,
+  "orientation": "hexagonal",
+  "renderorder": "right-down",
+  "staggeraxis": "{axis}", "staggerindex": "{index}",
+  "tileheight": 32, "tilewidth": 28,
+  "layers": [
+    {{
+      "id": 3, "name": "ground", "type": "group", "visible": true,
+      "layers": [
+        {{
+          "data": [1, 0, 2, 3, 0, 2147483652],
+          "height": 2, "width": 3, "id": 1, "name": "terrain",
+          "opacity": 1, "type": "tilelayer", "visible": true, "x": 0, "y": 0
+        }}
+      ]
+    }},
+    {{
+      "draworder": "topdown", "id": 2, "name": "units", "type": "objectgroup",
+      "objects": [
+        {{ "id": 1, "name": "king", "type": "unit", "x": 28, "y": 40,
+           "width": 0, "height": 0, "point": true, "rotation": 0, "visible": true }},
+        {{ "id": 2, "gid": 5, "name": "", "class": "tree", "x": 0, "y": 32,
+           "width": 28, "height": 32, "rotation": 0, "visible": true }}
+      ]
+    }}
+  ],
+  "tilesets": [ {{ "firstgid": 1, "source": "hexes.tsj" }} ],
+  "type": "map", "version": "1.10"
+}}"#
+        )
+    }
+
+    fn near(a: Vec2, b: Vec2) -> bool {
+        (a.x() - b.x()).abs() < 1e-3 && (a.y() - b.y()).abs() < 1e-3
+    }
+
+    /// pixel center of the tile as drawn by Tiled
+    fn tiled_center(axis: &str, index: &str, col: i32, row: i32) -> Vec2 {
+        let (w, h, side) = (28., 32., 16.);
+        let odd = if index == "odd" { 1 } else { 0 };
+        let (col_f, row_f) = (col as f32, row as f32);
+        if axis == "y" {
+            let shoved = if row & 1 == odd { w / 2. } else { 0. };
+            Vec2(
+                w / 2. + col_f * w + shoved,
+                h / 2. + row_f * (h + side) / 2.,
+            )
+        } else {
+            let shoved = if col & 1 == odd { h / 2. } else { 0. };
+            Vec2(
+                w / 2. + col_f * (w + side) / 2.,
+                h / 2. + row_f * h + shoved,
+            )
+        }
+    }
+
+    #[test]
+    fn stagger_settings() {
+        let cases = [
+            ("y", "odd", OffsetType::OddR),
+            ("y", "even", OffsetType::EvenR),
+            ("x", "odd", OffsetType::OddQ),
+            ("x", "even", OffsetType::EvenQ),
+        ];
+        for (axis, index, offset) in cases {
+            let tiled = TiledMap::from_json(&tmj(axis, index)).unwrap();
+            assert_eq!(tiled.offset, offset);
+            assert_eq!(tiled.layout.layout_type(), offset.layout_type());
+            assert_eq!(tiled.map.layout, offset.layout_type());
+            for col in 0..3 {
+                for row in 0..2 {
+                    let hex = tiled.tile_hex(col, row);
+                    assert!(tiled.map.shape.contains(hex), "{axis} {index}");
+                    let got = tiled.layout.hex_to_pxl(hex);
+                    let want = tiled_center(axis, index, col, row);
+                    assert!(near(got, want), "{axis} {index} {got:?} {want:?}");
+                    assert_eq!(tiled.layout.point_to_hex(want), hex);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn tile_and_object_layers() {
+        let tiled = TiledMap::from_json(&tmj("y", "odd")).unwrap();
+        let MapShape::Rect(rect) = tiled.map.shape else {
+            panic!("expected a rect");
+        };
+        assert_eq!((rect.width(), rect.height()), (3, 2));
+        let terrain = tiled.map.layer("ground/terrain").unwrap();
+        // empty tiles are skipped and the flip flag is dropped
+        let want = [(0, 0, 1), (2, 0, 2), (0, 1, 3), (2, 1, 4)];
+        assert_eq!(terrain.tiles.len(), want.len());
+        for (col, row, gid) in want {
+            assert!(terrain.tiles.contains(&(tiled.tile_hex(col, row), gid)));
+        }
+
+        assert_eq!(tiled.object_layers.len(), 1);
+        let units = &tiled.object_layers[0];
+        assert_eq!(units.name, "units");
+        let king = &units.objects[0];
+        assert_eq!(
+            (king.id, king.name.as_str(), king.class.as_str()),
+            (1, "king", "unit")
+        );
+        assert_eq!(king.hex, tiled.tile_hex(0, 1));
+        let tree = &units.objects[1];
+        assert_eq!((tree.class.as_str(), tree.gid), ("tree", Some(5)));
+        assert!(near(tree.position, Vec2(14., 16.)));
+        assert_eq!(tree.hex, IHEX_0);
+    }
+
+    #[test]
+    fn base64_data() {
+        let text = tmj("x", "even").replace(
+            r#""data": [1, 0, 2, 3, 0, 2147483652]"#,
+            r#""data": "AQAAAAAAAAACAAAAAwAAAAAAAAAEAACA", "encoding": "base64""#,
+        );
+        let tiled = TiledMap::from_json(&text).unwrap();
+        let csv = TiledMap::from_json(&tmj("x", "even")).unwrap();
+        assert_eq!(tiled.map.layers, csv.map.layers);
+        let MapShape::Rect(rect) = tiled.map.shape else {
+            panic!("expected a rect");
+        };
+        // even columns are shoved, the rect starts with an extra column
+        assert_eq!((rect.width(), rect.height()), (4, 2));
+    }
+
+    #[test]
+    fn errors() {
+        let bad_stagger = tmj("z", "odd");
+        let err = TiledMap::from_json(&bad_stagger).unwrap_err();
+        assert!(matches!(err, MapError::Syntax { line: 8, .. }), "{err}");
+        let ortho = tmj("y", "odd").replace("hexagonal", "orthogonal");
+        assert!(TiledMap::from_json(&ortho).is_err());
+        let short = tmj("y", "odd").replace("[1, 0, 2, 3, 0, 2147483652]", "[1, 2]");
+        let err = TiledMap::from_json(&short).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 15: layer `ground/terrain` has 2 tiles instead of 3x2"
+        );
+        let negative = tmj("y", "odd").replace("\"width\": 3", "\"width\": -3");
+        let err = TiledMap::from_json(&negative).unwrap_err();
+        assert!(err.to_string().contains("size -3 is outside of"), "{err}");
+        let huge = tmj("y", "odd").replace("\"height\": 2", "\"height\": 2147483647");
+        assert!(TiledMap::from_json(&huge).is_err());
+        let far = tmj("y", "odd").replace("\"x\": 0", "\"x\": 2147483647");
+        assert!(TiledMap::from_json(&far).is_err());
+        let truncated = &tmj("y", "odd")[..200];
+        assert!(TiledMap::from_json(truncated).is_err());
+    }
+
+    #[test]
+    fn nesting_limit() {
+        let deep = format!("{}{}", "[{\"a\": ".repeat(50_000), "0");
+        let err = TiledMap::from_json(&deep).unwrap_err();
+        assert_eq!(err.to_string(), "line 1: values are nested deeper than 64");
+        let nested = format!("{}1{}", "[".repeat(40), "]".repeat(40));
+        let text =
+            tmj("y", "odd").replace("\"tilewidth\": 28", &format!("\"tilewidth\": {nested}"));
+        let err = TiledMap::from_json(&text).unwrap_err();
+        assert!(matches!(err, MapError::Syntax { line: 9, .. }), "{err}");
+    }
+}
@@ -0,0 +1,147 @@
+use crate::camera::Camera;
+use crate::geometry::*;
+use crate::render::Renderer;
+use sdl2::pixels::Color;
+use sdl2::rect::FPoint;
+use sdl2::render::Vertex;
+
+/// Outlines of the visible grid as thin quads, one per edge, drawn in a single call.
+/// The mesh is rebuilt only when the camera layout changes or it is invalidated.
+#[derive(Debug, Clone)]
+pub struct GridMesh {
+    color: Color,
+    thickness: f32,
+    /// layout the mesh was built for
+    layout: Option<Layout>,
+    vertices: Vec<Vertex>,
+    indices: Vec<u32>,
+}
+
+impl GridMesh {
+    pub fn new(color: Color, thickness: f32) -> Self {
+        GridMesh {
+            color,
+            thickness,
+            layout: None,
+            vertices: Vec::new(),
+            indices: Vec::new(),
+        }
+    }
+
+    /// force a rebuild on the next update, e.g. when the map changes
+    pub fn invalidate(&mut self) {
+        self.layout = None;
+    }
+
+    /// number of edges in the mesh
+    pub fn edge_count(&self) -> usize {
+        self.vertices.len() / 4
+    }
+
+    /// rebuild the mesh if the camera moved since the last build, returns true when rebuilt
+    pub fn update(&mut self, camera: &Camera, map: &impl Shape) -> bool {
+        if self.layout == Some(*camera.layout()) {
+            return false;
+        }
+        let layout = *camera.layout();
+        let visible = camera.visible_rect();
+        let contains = |h: IHex| visible.contains(h) && map.contains(h);
+        self.vertices.clear();
+        self.indices.clear();
+        for hex in camera.visible_hexes(map) {
+            let corners = layout.grid_corners(layout.hex_to_pxl(hex));
+            for i in 0..6 {
+                // shared edges are drawn by the hex on the side of the first three directions
+                let dir = layout.edge_dir(i);
+                if dir.to_ind() < 3 || !contains(hex + dir) {
+                    self.push_edge(corners[i], corners[(i + 1) % 6]);
+                }
+            }
+        }
+        self.layout = Some(layout);
+        true
+    }
+
+    fn push_edge(&mut self, a: Vec2, b: Vec2) {
+        let d = b - a;
+        let n = Vec2(-d.y(), d.x()) * (self.thickness / 2. / d.length());
+        let first = self.vertices.len() as u32;
+        for p in [a + n, a - n, b - n, b + n] {
+            self.vertices.push(Vertex {
+                position: p.into(),
+                color: self.color,
+                tex_coord: FPoint::new(0., 0.),
+            });
+        }
+        self.indices
+            .extend_from_slice(&[first, first + 1, first + 2, first, first + 2, first + 3]);
+    }
+
+    pub fn draw(&self, renderer: &mut impl Renderer) -> Result<(), String> {
+        if self.indices.is_empty() {
+            return Ok(());
+        }
+        renderer.draw_geometry(&self.vertices, &self.indices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::camera::Camera;
+    use crate::geometry::*;
+    use crate::render::GridMesh;
+    use sdl2::pixels::Color;
+    use std::collections::HashSet;
+
+    /// unique edges counted by their rounded end points
+    fn unique_edges(layout: &Layout, hexes: impl Iterator<Item = IHex>) -> usize {
+        let key = |p: Vec2| ((p.x() * 8.).round() as i64, (p.y() * 8.).round() as i64);
+        let mut edges = HashSet::new();
+        for hex in hexes {
+            let c = layout.grid_corners(layout.hex_to_pxl(hex));
+            for i in 0..6 {
+                let (a, b) = (key(c[i]), key(c[(i + 1) % 6]));
+                edges.insert(if a < b { (a, b) } else { (b, a) });
+            }
+        }
+        edges.len()
+    }
+
+    #[test]
+    fn shared_edges_drawn_once() {
+        for orientation in [ORIENTATION_POINTY, ORIENTATION_FLAT] {
+            let layout = Layout::new(orientation, Vec2(12., 10.), Vec2(40., 30.));
+            let camera = Camera::new(layout, Vec2(400., 300.));
+            for map in [Rect::new(IHEX_0, 6, 5), Rect::new(IHEX_0, 200, 200)] {
+                let mut grid = GridMesh::new(Color::BLACK, 1.);
+                assert!(grid.update(&camera, &map));
+                let want = unique_edges(&layout, camera.visible_hexes(&map));
+                assert_eq!(grid.edge_count(), want);
+                assert_eq!(grid.indices.len(), want * 6);
+            }
+        }
+    }
+
+    #[test]
+    fn single_hex() {
+        let layout = Layout::new(ORIENTATION_POINTY, Vec2(12., 10.), Vec2(40., 30.));
+        let camera = Camera::new(layout, Vec2(400., 300.));
+        let mut grid = GridMesh::new(Color::BLACK, 1.);
+        grid.update(&camera, &Point(IHEX_0));
+        assert_eq!(grid.edge_count(), 6);
+    }
+
+    #[test]
+    fn cached_until_camera_moves() {
+        let layout = Layout::new(ORIENTATION_FLAT, Vec2(12., 10.), Vec2(0., 0.));
+        let mut camera = Camera::new(layout, Vec2(400., 300.));
+        let map = Rect::with_type(IHEX_0, 50, 50, LayoutType::Flat);
+        let mut grid = GridMesh::new(Color::BLACK, 1.);
+        assert!(grid.update(&camera, &map));
+        assert!(!grid.update(&camera, &map));
+        camera.pan(Vec2(-5., 0.));
+        assert!(grid.update(&camera, &map));
+        grid.invalidate();
+        assert!(grid.update(&camera, &map));
+    }
+}
@@ -0,0 +1,252 @@
+use crate::geometry::*;
+use crate::render::Renderer;
+use sdl2::pixels::Color;
+use sdl2::rect::FPoint;
+use sdl2::render::{Canvas, RenderTarget, Texture, Vertex};
+
+/// Grid of equally sized sprites in a texture, numbered row by row.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpriteAtlas {
+    width: u32,
+    height: u32,
+    cell_w: u32,
+    cell_h: u32,
+}
+
+impl SpriteAtlas {
+    /// atlas of a `width` x `height` texture cut into `cell_w` x `cell_h` sprites
+    pub fn new(width: u32, height: u32, cell_w: u32, cell_h: u32) -> Self {
+        assert!(cell_w > 0 && cell_h > 0, "sprite cell must not be empty");
+        SpriteAtlas {
+            width,
+            height,
+            cell_w,
+            cell_h,
+        }
+    }
+
+    /// whole texture as a single sprite
+    pub fn single(width: u32, height: u32) -> Self {
+        Self::new(width, height, width, height)
+    }
+
+    pub fn len(&self) -> usize {
+        self.columns() as usize * (self.height / self.cell_h) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn columns(&self) -> u32 {
+        self.width / self.cell_w
+    }
+
+    /// normalized texture coordinates of the sprite, top-left and bottom-right corners
+    pub fn uv(&self, sprite: usize) -> Option<(FPoint, FPoint)> {
+        if sprite >= self.len() {
+            return None;
+        }
+        let col = sprite as u32 % self.columns();
+        let row = sprite as u32 / self.columns();
+        let (w, h) = (self.width as f32, self.height as f32);
+        let min = FPoint::new(
+            (col * self.cell_w) as f32 / w,
+            (row * self.cell_h) as f32 / h,
+        );
+        let max = FPoint::new(
+            ((col + 1) * self.cell_w) as f32 / w,
+            ((row + 1) * self.cell_h) as f32 / h,
+        );
+        Some((min, max))
+    }
+}
+
+/// Triangles of filled hexes, every hex is a fan of six triangles around its center.
+#[derive(Debug, Clone, Default)]
+pub struct HexMesh {
+    vertices: Vec<Vertex>,
+    indices: Vec<u32>,
+}
+
+impl HexMesh {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+        self.indices.clear();
+    }
+
+    pub fn vertices(&self) -> &[Vertex] {
+        &self.vertices
+    }
+
+    pub fn indices(&self) -> &[u32] {
+        &self.indices
+    }
+
+    /// add a hex filled with the color
+    pub fn push_hex(&mut self, layout: &Layout, hex: IHex, color: Color) {
+        self.push_fan(layout, hex, color, None);
+    }
+
+    /// add a hex textured with the sprite, the sprite cell is stretched over the hex bounding box
+    pub fn push_sprite(&mut self, layout: &Layout, hex: IHex, atlas: &SpriteAtlas, sprite: usize) {
+        if let Some(uv) = atlas.uv(sprite) {
+            self.push_fan(layout, hex, Color::WHITE, Some(uv));
+        }
+    }
+
+    fn push_fan(&mut self, layout: &Layout, hex: IHex, color: Color, uv: Option<(FPoint, FPoint)>) {
+        let center = layout.hex_to_pxl(hex);
+        let size = layout.get_size();
+        // map a point of the hex bounding box into the sprite cell
+        let tex_coord = |p: Vec2| match uv {
+            Some((min, max)) => {
+                let tx = ((p.x() - center.x()) / size.x() + 1.) / 2.;
+                let ty = ((p.y() - center.y()) / size.y() + 1.) / 2.;
+                FPoint::new(
+                    min.x() + (max.x() - min.x()) * tx,
+                    min.y() + (max.y() - min.y()) * ty,
+                )
+            }
+            None => FPoint::new(0., 0.),
+        };
+        let first = self.vertices.len() as u32;
+        let vertex = |p: Vec2| Vertex {
+            position: p.into(),
+            color,
+            tex_coord: tex_coord(p),
+        };
+        self.vertices.push(vertex(center));
+        for corner in layout.grid_corners(center) {
+            self.vertices.push(vertex(corner));
+        }
+        for i in 0..6 {
+            self.indices
+                .extend_from_slice(&[first, first + 1 + i, first + 1 + (i + 1) % 6]);
+        }
+    }
+
+    /// submit all triangles in a single draw call
+    pub fn draw(&self, renderer: &mut impl Renderer) -> Result<(), String> {
+        if self.indices.is_empty() {
+            return Ok(());
+        }
+        renderer.draw_geometry(&self.vertices, &self.indices)
+    }
+
+    /// submit all triangles textured with sprites in a single draw call
+    pub fn draw_textured<T: RenderTarget>(
+        &self,
+        canvas: &mut Canvas<T>,
+        texture: &Texture,
+    ) -> Result<(), String> {
+        if self.indices.is_empty() {
+            return Ok(());
+        }
+        canvas.render_geometry(&self.vertices, Some(texture), self.indices.as_slice())
+    }
+}
+
+/// fill every hex with the color given by the callback, hexes without color are skipped
+pub fn fill_hexes(
+    renderer: &mut impl Renderer,
+    layout: &Layout,
+    hexes: impl Iterator<Item = IHex>,
+    color: impl Fn(IHex) -> Option<Color>,
+) -> Result<(), String> {
+    let mut mesh = HexMesh::new();
+    for hex in hexes {
+        if let Some(c) = color(hex) {
+            mesh.push_hex(layout, hex, c);
+        }
+    }
+    mesh.draw(renderer)
+}
+
+/// draw sprites of the atlas on hexes, hexes without sprite are skipped
+pub fn draw_sprites<T: RenderTarget>(
+    canvas: &mut Canvas<T>,
+    layout: &Layout,
+    texture: &Texture,
+    atlas: &SpriteAtlas,
+    hexes: impl Iterator<Item = IHex>,
+    sprite: impl Fn(IHex) -> Option<usize>,
+) -> Result<(), String> {
+    let mut mesh = HexMesh::new();
+    for hex in hexes {
+        if let Some(s) = sprite(hex) {
+            mesh.push_sprite(layout, hex, atlas, s);
+        }
+    }
+    mesh.draw_textured(canvas, texture)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::*;
+    use crate::render::{HexMesh, SpriteAtlas};
+    use sdl2::pixels::Color;
+    use sdl2::rect::FPoint;
+
+    #[test]
+    fn atlas_uv() {
+        let atlas = SpriteAtlas::new(128, 64, 32, 32);
+        assert_eq!(atlas.len(), 8);
+        assert_eq!(
+            atlas.uv(0),
+            Some((FPoint::new(0., 0.), FPoint::new(0.25, 0.5)))
+        );
+        assert_eq!(
+            atlas.uv(5),
+            Some((FPoint::new(0.25, 0.5), FPoint::new(0.5, 1.)))
+        );
+        assert_eq!(atlas.uv(8), None);
+        let single = SpriteAtlas::single(10, 10);
+        assert_eq!(
+            single.uv(0),
+            Some((FPoint::new(0., 0.), FPoint::new(1., 1.)))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "sprite cell must not be empty")]
+    fn atlas_empty_cell() {
+        SpriteAtlas::new(128, 64, 0, 32);
+    }
+
+    #[test]
+    fn fan_per_hex() {
+        let layout = Layout::new(ORIENTATION_POINTY, Vec2(10., 10.), Vec2(5., 5.));
+        let mut mesh = HexMesh::new();
+        mesh.push_hex(&layout, IHex(0, 0), Color::RED);
+        mesh.push_hex(&layout, IHex(1, 0), Color::BLUE);
+        assert_eq!(mesh.vertices().len(), 14);
+        assert_eq!(mesh.indices().len(), 36);
+        assert!(mesh.indices().iter().all(|&i| (i as usize) < 14));
+        assert_eq!(
+            mesh.vertices()[0].position,
+            layout.hex_to_pxl(IHex(0, 0)).into()
+        );
+        assert_eq!(mesh.vertices()[7].color, Color::BLUE);
+    }
+
+    #[test]
+    fn sprite_tex_coords() {
+        let layout = Layout::new(ORIENTATION_FLAT, Vec2(10., 10.), Vec2(0., 0.));
+        let atlas = SpriteAtlas::new(64, 32, 32, 32);
+        let mut mesh = HexMesh::new();
+        mesh.push_sprite(&layout, IHex(2, 1), &atlas, 1);
+        mesh.push_sprite(&layout, IHex(2, 1), &atlas, 7);
+        assert_eq!(mesh.vertices().len(), 7);
+        let center = mesh.vertices()[0].tex_coord;
+        assert!((center.x() - 0.75).abs() < 1e-4 && (center.y() - 0.5).abs() < 1e-4);
+        for v in &mesh.vertices()[1..] {
+            assert!(v.tex_coord.x() >= 0.5 - 1e-4 && v.tex_coord.x() <= 1. + 1e-4);
+            assert!(v.tex_coord.y() >= -1e-4 && v.tex_coord.y() <= 1. + 1e-4);
+        }
+    }
+}
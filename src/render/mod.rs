@@ -0,0 +1,9 @@
+mod grid;
+mod mesh;
+mod renderer;
+mod software;
+
+pub use grid::*;
+pub use mesh::*;
+pub use renderer::*;
+pub use software::*;
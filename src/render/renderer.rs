@@ -0,0 +1,40 @@
+use sdl2::pixels::Color;
+use sdl2::rect::FPoint;
+use sdl2::render::{Canvas, RenderTarget, Vertex};
+
+/// Drawing primitives the map rendering is built on.
+/// Textured drawing stays on the SDL canvas, since textures live on the GPU.
+pub trait Renderer {
+    /// size of the drawing area in pixels
+    fn size(&self) -> (u32, u32);
+
+    /// fill the whole drawing area with the color
+    fn clear(&mut self, color: Color) -> Result<(), String>;
+
+    /// connected line segments through all points
+    fn draw_lines(&mut self, points: &[FPoint], color: Color) -> Result<(), String>;
+
+    /// triangles given by triples of indices into vertices, colors are interpolated
+    fn draw_geometry(&mut self, vertices: &[Vertex], indices: &[u32]) -> Result<(), String>;
+}
+
+impl<T: RenderTarget> Renderer for Canvas<T> {
+    fn size(&self) -> (u32, u32) {
+        self.output_size().unwrap_or((0, 0))
+    }
+
+    fn clear(&mut self, color: Color) -> Result<(), String> {
+        self.set_draw_color(color);
+        Canvas::clear(self);
+        Ok(())
+    }
+
+    fn draw_lines(&mut self, points: &[FPoint], color: Color) -> Result<(), String> {
+        self.set_draw_color(color);
+        self.draw_flines(points)
+    }
+
+    fn draw_geometry(&mut self, vertices: &[Vertex], indices: &[u32]) -> Result<(), String> {
+        self.render_geometry(vertices, None, indices)
+    }
+}
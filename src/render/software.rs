@@ -0,0 +1,346 @@
+use crate::render::Renderer;
+use sdl2::pixels::Color;
+use sdl2::rect::FPoint;
+use sdl2::render::Vertex;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Renderer rasterizing into an RGBA buffer in memory, works without a display.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoftwareRenderer {
+    width: u32,
+    height: u32,
+    /// RGBA, row by row from the top
+    pixels: Vec<u8>,
+}
+
+impl SoftwareRenderer {
+    pub fn new(width: u32, height: u32) -> Self {
+        SoftwareRenderer {
+            width,
+            height,
+            pixels: vec![0; Self::offset(width, 0, height)],
+        }
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> Option<Color> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let i = Self::offset(self.width, x, y);
+        let p = &self.pixels[i..i + 4];
+        Some(Color::RGBA(p[0], p[1], p[2], p[3]))
+    }
+
+    /// index of the first byte of the pixel in a buffer `width` pixels wide
+    fn offset(width: u32, x: u32, y: u32) -> usize {
+        (y as usize)
+            .checked_mul(width as usize)
+            .and_then(|i| i.checked_add(x as usize)?.checked_mul(4))
+            .expect("image size overflows the address space")
+    }
+
+    /// blend the color over the pixel, pixels outside are ignored
+    fn blend(&mut self, x: i32, y: i32, c: Color) {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return;
+        }
+        let i = Self::offset(self.width, x as u32, y as u32);
+        let p = &mut self.pixels[i..i + 4];
+        let a = c.a as u32;
+        let mix = |dst: u8, src: u8| ((src as u32 * a + dst as u32 * (255 - a)) / 255) as u8;
+        p[0] = mix(p[0], c.r);
+        p[1] = mix(p[1], c.g);
+        p[2] = mix(p[2], c.b);
+        p[3] = (a + p[3] as u32 * (255 - a) / 255) as u8;
+    }
+
+    fn fill_triangle(&mut self, v: [&Vertex; 3]) {
+        let [p0, p1, p2] = v.map(|v| v.position);
+        let area = edge(p0, p1, p2);
+        if area == 0. {
+            return;
+        }
+        // clockwise and counterclockwise triangles are both filled
+        let sign = area.signum();
+        let min_x = p0.x().min(p1.x()).min(p2.x()).floor().max(0.) as i32;
+        let min_y = p0.y().min(p1.y()).min(p2.y()).floor().max(0.) as i32;
+        let max_x = (p0.x().max(p1.x()).max(p2.x()).ceil() as i32).min(self.width as i32 - 1);
+        let max_y = (p0.y().max(p1.y()).max(p2.y()).ceil() as i32).min(self.height as i32 - 1);
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let p = FPoint::new(x as f32 + 0.5, y as f32 + 0.5);
+                let w = [edge(p1, p2, p), edge(p2, p0, p), edge(p0, p1, p)].map(|w| w * sign);
+                let edges = [(p1, p2), (p2, p0), (p0, p1)];
+                // pixels exactly on a shared edge belong to a single triangle
+                let inside = w
+                    .iter()
+                    .zip(edges)
+                    .all(|(&w, (a, b))| w > 0. || (w == 0. && is_top_left(a, b, sign)));
+                if inside {
+                    let c = interpolate(v, w, area.abs());
+                    self.blend(x, y, c);
+                }
+            }
+        }
+    }
+
+    fn draw_line(&mut self, a: FPoint, b: FPoint, c: Color) {
+        let (dx, dy) = (b.x() - a.x(), b.y() - a.y());
+        let steps = dx.abs().max(dy.abs()).ceil().max(1.) as i32;
+        for i in 0..=steps {
+            let t = i as f32 / steps as f32;
+            let x = (a.x() + dx * t).floor() as i32;
+            let y = (a.y() + dy * t).floor() as i32;
+            self.blend(x, y, c);
+        }
+    }
+
+    /// binary PPM (P6) image, alpha is dropped
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut out = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        for p in self.pixels.chunks(4) {
+            out.extend_from_slice(&p[..3]);
+        }
+        out
+    }
+
+    /// RGBA PNG image, the image data is stored without compression
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut raw = Vec::with_capacity(self.pixels.len() + self.height as usize);
+        for row in self.pixels.chunks(Self::offset(self.width, 0, 1).max(1)) {
+            raw.push(0); // no filter
+            raw.extend_from_slice(row);
+        }
+        let mut ihdr = Vec::new();
+        ihdr.extend_from_slice(&self.width.to_be_bytes());
+        ihdr.extend_from_slice(&self.height.to_be_bytes());
+        ihdr.extend_from_slice(&[8, 6, 0, 0, 0]); // 8 bit RGBA
+        let mut out = vec![0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+        png_chunk(&mut out, b"IHDR", &ihdr);
+        png_chunk(&mut out, b"IDAT", &zlib_stored(&raw));
+        png_chunk(&mut out, b"IEND", &[]);
+        out
+    }
+
+    pub fn save_ppm(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_ppm())
+    }
+
+    pub fn save_png(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_png())
+    }
+}
+
+impl Renderer for SoftwareRenderer {
+    fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    fn clear(&mut self, color: Color) -> Result<(), String> {
+        for p in self.pixels.chunks_mut(4) {
+            p.copy_from_slice(&[color.r, color.g, color.b, color.a]);
+        }
+        Ok(())
+    }
+
+    fn draw_lines(&mut self, points: &[FPoint], color: Color) -> Result<(), String> {
+        for w in points.windows(2) {
+            self.draw_line(w[0], w[1], color);
+        }
+        Ok(())
+    }
+
+    fn draw_geometry(&mut self, vertices: &[Vertex], indices: &[u32]) -> Result<(), String> {
+        if !indices.len().is_multiple_of(3) {
+            return Err(format!("{} indices do not form triangles", indices.len()));
+        }
+        for t in indices.chunks(3) {
+            let v = |i: u32| {
+                vertices
+                    .get(i as usize)
+                    .ok_or_else(|| format!("vertex index {i} is out of bounds"))
+            };
+            self.fill_triangle([v(t[0])?, v(t[1])?, v(t[2])?]);
+        }
+        Ok(())
+    }
+}
+
+/// doubled signed area of the triangle, positive for clockwise order on screen
+#[inline]
+fn edge(a: FPoint, b: FPoint, p: FPoint) -> f32 {
+    (b.x() - a.x()) * (p.y() - a.y()) - (b.y() - a.y()) * (p.x() - a.x())
+}
+
+/// top and left edges own the pixels lying exactly on them
+#[inline]
+fn is_top_left(a: FPoint, b: FPoint, sign: f32) -> bool {
+    let (dx, dy) = ((b.x() - a.x()) * sign, (b.y() - a.y()) * sign);
+    (dy == 0. && dx > 0.) || dy < 0.
+}
+
+fn interpolate(v: [&Vertex; 3], w: [f32; 3], area: f32) -> Color {
+    let mix = |f: fn(&Color) -> u8| {
+        let sum: f32 = (0..3).map(|i| f(&v[i].color) as f32 * w[i]).sum();
+        (sum / area).round().clamp(0., 255.) as u8
+    };
+    Color::RGBA(mix(|c| c.r), mix(|c| c.g), mix(|c| c.b), mix(|c| c.a))
+}
+
+fn png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// zlib stream of uncompressed deflate blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(0xffff).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none() as u8;
+        let len = block.len() as u16;
+        out.push(last);
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    out.extend_from_slice(&((b << 16) | a).to_be_bytes());
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::camera::Camera;
+    use crate::geometry::*;
+    use crate::render::software::crc32;
+    use crate::render::{GridMesh, HexMesh, Renderer, SoftwareRenderer};
+    use sdl2::pixels::Color;
+    use sdl2::rect::FPoint;
+
+    const GOLDEN_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden");
+
+    /// compare with the golden image, tolerating a few pixels of rounding differences;
+    /// run with UPDATE_GOLDEN=1 to rewrite the golden images
+    fn assert_golden(name: &str, img: &SoftwareRenderer) {
+        let path = format!("{GOLDEN_DIR}/{name}.ppm");
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            std::fs::create_dir_all(GOLDEN_DIR).unwrap();
+            img.save_ppm(&path).unwrap();
+        }
+        let want = std::fs::read(&path).unwrap_or_else(|e| panic!("{path}: {e}"));
+        let got = img.to_ppm();
+        assert_eq!(want.len(), got.len(), "{name}: image size differs");
+        let diff = want.iter().zip(&got).filter(|(a, b)| a != b).count();
+        assert!(diff * 1000 < got.len(), "{name}: {diff} bytes differ");
+    }
+
+    fn render_grid(orientation: Orientation) -> SoftwareRenderer {
+        let layout = Layout::new(orientation, Vec2(12., 12.), Vec2(20., 20.));
+        let camera = Camera::new(layout, Vec2(160., 120.));
+        let map = Rect::with_type(IHEX_0, 8, 6, orientation.layout_type());
+        let mut img = SoftwareRenderer::new(160, 120);
+        img.clear(Color::RGB(197, 195, 146)).unwrap();
+        let mut fill = HexMesh::new();
+        for hex in camera.visible_hexes(&map) {
+            let shade = (hex.distance(IHEX_0) * 12).min(100) as u8;
+            fill.push_hex(&layout, hex, Color::RGB(90, 140 + shade, 90));
+        }
+        fill.draw(&mut img).unwrap();
+        let mut grid = GridMesh::new(Color::BLACK, 1.);
+        grid.update(&camera, &map);
+        grid.draw(&mut img).unwrap();
+        img
+    }
+
+    #[test]
+    fn golden_grid_pointy() {
+        assert_golden("grid_pointy", &render_grid(ORIENTATION_POINTY));
+    }
+
+    #[test]
+    fn golden_grid_flat() {
+        assert_golden("grid_flat", &render_grid(ORIENTATION_FLAT));
+    }
+
+    #[test]
+    fn triangles_share_edges() {
+        let mut img = SoftwareRenderer::new(8, 8);
+        let layout = Layout::new(ORIENTATION_POINTY, Vec2(3., 3.), Vec2(4., 4.));
+        let mut mesh = HexMesh::new();
+        mesh.push_hex(&layout, IHEX_0, Color::RGBA(255, 0, 0, 128));
+        mesh.draw(&mut img).unwrap();
+        // pixels on the inner edges of the fan are not blended twice
+        let center = img.pixel(4, 4).unwrap();
+        assert_eq!(center, img.pixel(4, 3).unwrap());
+        assert_eq!(center, img.pixel(3, 4).unwrap());
+        assert_eq!(center.r, 128);
+        assert_eq!(img.pixel(0, 0), Some(Color::RGBA(0, 0, 0, 0)));
+    }
+
+    #[test]
+    fn lines() {
+        let mut img = SoftwareRenderer::new(4, 4);
+        let points = [
+            FPoint::new(0., 0.),
+            FPoint::new(3., 3.),
+            FPoint::new(3., 0.),
+        ];
+        img.draw_lines(&points, Color::WHITE).unwrap();
+        for i in 0..4 {
+            assert_eq!(img.pixel(i, i), Some(Color::WHITE));
+            assert_eq!(img.pixel(3, i), Some(Color::WHITE));
+        }
+        assert_eq!(img.pixel(0, 3), Some(Color::RGBA(0, 0, 0, 0)));
+    }
+
+    #[test]
+    fn image_formats() {
+        let mut img = SoftwareRenderer::new(3, 2);
+        img.clear(Color::RGB(1, 2, 3)).unwrap();
+        let ppm = img.to_ppm();
+        assert!(ppm.starts_with(b"P6\n3 2\n255\n"));
+        assert_eq!(&ppm[ppm.len() - 3..], &[1, 2, 3]);
+        let png = img.to_png();
+        assert_eq!(&png[..8], &[0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a]);
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(&png[png.len() - 8..png.len() - 4], b"IEND");
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+    }
+
+    #[test]
+    fn bad_indices() {
+        let mut img = SoftwareRenderer::new(4, 4);
+        assert!(img.draw_geometry(&[], &[0, 1]).is_err());
+        assert!(img.draw_geometry(&[], &[0, 1, 2]).is_err());
+    }
+}
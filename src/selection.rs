@@ -0,0 +1,194 @@
+use crate::geometry::*;
+use std::collections::HashSet;
+
+/// Area selected by dragging from one hex to another.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DragShape {
+    /// hexagon around the start hex reaching the current hex
+    Hexagon,
+    /// rectangle with the start and the current hex in its corners
+    Rect(LayoutType),
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Drag {
+    start: IHex,
+    current: IHex,
+    shape: DragShape,
+}
+
+/// Set of selected hexes built with clicks and drags.
+///
+/// A press and release on the same hex is a click selecting the tile, or a line
+/// from the last clicked tile when extended. Moving to another hex while pressed
+/// turns it into a drag selecting the whole area on release.
+#[derive(Clone, Debug, Default)]
+pub struct Selection {
+    hexes: HashSet<IHex>,
+    /// last clicked hex, start of the line selections
+    anchor: Option<IHex>,
+    drag: Option<Drag>,
+}
+
+impl Selection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn hexes(&self) -> &HashSet<IHex> {
+        &self.hexes
+    }
+
+    pub fn contains(&self, hex: IHex) -> bool {
+        self.hexes.contains(&hex)
+    }
+
+    pub fn len(&self) -> usize {
+        self.hexes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hexes.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.hexes.clear();
+        self.anchor = None;
+        self.drag = None;
+    }
+
+    /// replace the selection with the single hex
+    pub fn select(&mut self, hex: IHex) {
+        self.hexes = HashSet::from([hex]);
+        self.anchor = Some(hex);
+    }
+
+    /// replace the selection with the line from the last clicked hex,
+    /// the anchor stays so the line can be aimed again
+    pub fn select_line(&mut self, hex: IHex) {
+        let from = *self.anchor.get_or_insert(hex);
+        self.hexes = from.line(hex).collect();
+    }
+
+    /// replace the selection with the hexes of the shape
+    pub fn select_shape(&mut self, shape: &impl Shape) {
+        self.hexes = shape.hex_iter().collect();
+    }
+
+    /// mouse button went down over the hex
+    pub fn press(&mut self, hex: IHex, shape: DragShape) {
+        self.drag = Some(Drag {
+            start: hex,
+            current: hex,
+            shape,
+        });
+    }
+
+    /// pointer moved over the hex while pressed
+    pub fn drag_to(&mut self, hex: IHex) {
+        if let Some(drag) = &mut self.drag {
+            drag.current = hex;
+        }
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some_and(|d| d.start != d.current)
+    }
+
+    /// mouse button went up, `line` extends a click into a line selection
+    pub fn release(&mut self, line: bool) {
+        let Some(drag) = self.drag.take() else {
+            return;
+        };
+        if drag.start != drag.current {
+            self.hexes = drag_hexes(drag).collect();
+        } else if line {
+            self.select_line(drag.start);
+        } else {
+            self.select(drag.start);
+        }
+    }
+
+    /// hexes the current drag would select, empty without a drag
+    pub fn preview(&self) -> Vec<IHex> {
+        match self.drag {
+            Some(drag) if drag.start != drag.current => drag_hexes(drag).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn drag_hexes(drag: Drag) -> Box<dyn Iterator<Item = IHex>> {
+    match drag.shape {
+        DragShape::Hexagon => {
+            let radius = drag.start.distance(drag.current);
+            Box::new(drag.start.spiral(radius))
+        }
+        DragShape::Rect(t) => {
+            let rect = Rect::from_corners(drag.start, drag.current, t);
+            Box::new((0..rect.area()).filter_map(move |i| rect.hex_by_ind(i)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::*;
+    use crate::selection::{DragShape, Selection};
+
+    #[test]
+    fn click_and_line() {
+        let mut sel = Selection::new();
+        sel.press(IHex(1, 1), DragShape::Hexagon);
+        sel.release(false);
+        assert_eq!(sel.len(), 1);
+        assert!(sel.contains(IHex(1, 1)));
+        sel.press(IHex(4, -1), DragShape::Hexagon);
+        sel.release(true);
+        assert_eq!(sel.len(), 4);
+        assert!(sel.contains(IHex(1, 1)) && sel.contains(IHex(4, -1)));
+        // the line starts at the last clicked hex again
+        sel.press(IHex(1, 3), DragShape::Hexagon);
+        sel.release(true);
+        assert_eq!(sel.len(), 3);
+        assert!(!sel.contains(IHex(4, -1)));
+        sel.clear();
+        sel.press(IHex(0, 0), DragShape::Hexagon);
+        sel.release(true);
+        assert_eq!(sel.len(), 1);
+    }
+
+    #[test]
+    fn drag_hexagon() {
+        let mut sel = Selection::new();
+        sel.press(IHex(2, 2), DragShape::Hexagon);
+        assert!(!sel.is_dragging());
+        sel.drag_to(IHex(4, 2));
+        assert!(sel.is_dragging());
+        assert_eq!(sel.preview().len(), 19);
+        assert!(sel.is_empty());
+        sel.release(true);
+        let want = Hexagon::new(IHex(2, 2), 2);
+        assert_eq!(sel.len(), want.area());
+        assert!(want.hex_iter().all(|h| sel.contains(h)));
+        assert!(sel.preview().is_empty());
+    }
+
+    #[test]
+    fn drag_rect() {
+        let mut sel = Selection::new();
+        sel.press(IHex(3, 4), DragShape::Rect(LayoutType::Pointy));
+        sel.drag_to(IHex(0, 0));
+        sel.drag_to(IHex(-1, 0));
+        sel.release(false);
+        let want = Rect::from_corners(IHex(3, 4), IHex(-1, 0), LayoutType::Pointy);
+        assert_eq!(sel.len(), want.area());
+        assert!(want.hex_iter().all(|h| sel.contains(h)));
+        // moving back to the start is a click
+        sel.press(IHex(3, 4), DragShape::Rect(LayoutType::Pointy));
+        sel.drag_to(IHex(5, 4));
+        sel.drag_to(IHex(3, 4));
+        sel.release(false);
+        assert_eq!(sel.len(), 1);
+    }
+}